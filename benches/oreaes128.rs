@@ -45,6 +45,16 @@ fn do_encrypt_left_32(input: u32, ore: &mut OreAes128ChaCha20) {
     input.encrypt_left(ore).unwrap();
 }
 
+#[inline]
+fn do_encrypt_128(input: u128, ore: &mut OreAes128ChaCha20) {
+    input.encrypt(ore).unwrap();
+}
+
+#[inline]
+fn do_encrypt_left_128(input: u128, ore: &mut OreAes128ChaCha20) {
+    input.encrypt_left(ore).unwrap();
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let k1 = hex!("00010203 04050607 08090a0b 0c0d0e0f");
     let k2 = hex!("d0d007a5 3f9a6848 83bc1f21 0f6595a3");
@@ -59,6 +69,14 @@ fn criterion_benchmark(c: &mut Criterion) {
     let x_u32 = 100_u32.encrypt(&ore).unwrap();
     let y_u32 = 10098393_u32.encrypt(&ore).unwrap();
 
+    let x_u128 = 100_u128.encrypt(&ore).unwrap();
+    let y_u128 = 340282366920938463463374607431768211_u128
+        .encrypt(&ore)
+        .unwrap();
+
+    let x_u128_bytes = x_u128.to_bytes();
+    let y_u128_bytes = y_u128.to_bytes();
+
     c.bench_function("encrypt-8", |b| {
         b.iter(|| do_encrypt_64(25u64, black_box(&mut ore)))
     });
@@ -87,6 +105,19 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("compare-4", |b| {
         b.iter(|| do_compare(black_box(&x_u32), black_box(&y_u32)))
     });
+
+    c.bench_function("encrypt-16", |b| {
+        b.iter(|| do_encrypt_128(25u128, black_box(&mut ore)))
+    });
+    c.bench_function("encrypt-left-16", |b| {
+        b.iter(|| do_encrypt_left_128(25u128, black_box(&mut ore)))
+    });
+    c.bench_function("compare-16", |b| {
+        b.iter(|| do_compare(black_box(&x_u128), black_box(&y_u128)))
+    });
+    c.bench_function("compare-16-slice", |b| {
+        b.iter(|| do_compare_slice(black_box(&x_u128_bytes), black_box(&y_u128_bytes)))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
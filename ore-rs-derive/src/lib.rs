@@ -0,0 +1,204 @@
+//! Derive macro for encrypting structs as ordered composite keys with
+//! [ore-rs](https://crates.io/crates/ore-rs).
+//!
+//! Use it through the `derive` feature of `ore-rs`:
+//!
+//! ```rust
+//! use ore_rs::{scheme::bit2::OreAes128ChaCha20, OreCipher, OreEncrypt};
+//! # use hex_literal::hex;
+//!
+//! #[derive(OreEncrypt)]
+//! struct Event {
+//!     tenant_id: u32,
+//!     #[ore(desc)]
+//!     created_at: i64,
+//! }
+//!
+//! # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+//! let a = Event { tenant_id: 1, created_at: 200 }.encrypt(&ore).unwrap();
+//! let b = Event { tenant_id: 1, created_at: 100 }.encrypt(&ore).unwrap();
+//! let c = Event { tenant_id: 2, created_at: 300 }.encrypt(&ore).unwrap();
+//!
+//! // Newest first within a tenant
+//! assert!(a < b);
+//! assert!(b < c);
+//! ```
+//!
+//! The macro implements `OrePlaintext` for the struct, which in turn provides `OreEncrypt`.
+//! Fields are compared in declaration order and every field must implement `OrePlaintext`: the
+//! types supported by `OreEncrypt`, other structs deriving `OreEncrypt` or custom implementations.
+//! Fields marked with `#[ore(desc)]` sort in descending order.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, Index};
+
+#[proc_macro_derive(OreEncrypt, attributes(ore))]
+pub fn derive_ore_encrypt(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct Field {
+    /* The member used to access the field, i.e. `self.#member` */
+    member: TokenStream2,
+    /* A local variable name for the field when decoding */
+    binding: proc_macro2::Ident,
+    ty: syn::Type,
+    desc: bool,
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    /*
+     * The size of the ciphertext is the sum of the sizes of the fields which can't be computed
+     * for generic fields on stable Rust
+     */
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "OreEncrypt can't be derived for generic types",
+        ));
+    }
+
+    let data = match &input.data {
+        Data::Struct(data) => data,
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "OreEncrypt can only be derived for structs",
+            ))
+        }
+    };
+
+    let fields = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let member = match &field.ident {
+                Some(ident) => quote!(#ident),
+                None => {
+                    let index = Index::from(i);
+                    quote!(#index)
+                }
+            };
+
+            Ok(Field {
+                member,
+                binding: format_ident!("field_{}", i),
+                ty: field.ty.clone(),
+                desc: is_desc(field)?,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    if fields.is_empty() {
+        return Err(Error::new(
+            input.ident.span(),
+            "OreEncrypt can't be derived for structs without fields",
+        ));
+    }
+
+    let name = &input.ident;
+    let private = quote!(::ore_rs::__private);
+    let sizes = fields
+        .iter()
+        .map(|Field { ty, .. }| quote!(<#ty as #private::OrderedBytes>::SIZE));
+
+    let writes = fields.iter().map(
+        |Field {
+             member, ty, desc, ..
+         }| {
+            let write = if *desc {
+                quote!(#private::write_desc(&self.#member, &mut out[offset..end]))
+            } else {
+                quote!(#private::OrderedBytes::write_ordered(&self.#member, &mut out[offset..end]))
+            };
+
+            quote! {
+                let end = offset + <#ty as #private::OrderedBytes>::SIZE;
+                #write;
+                let offset = end;
+            }
+        },
+    );
+
+    let reads = fields.iter().map(
+        |Field {
+             binding, ty, desc, ..
+         }| {
+            let read = if *desc {
+                quote!(#private::read_desc::<#ty>(&data[offset..end])?)
+            } else {
+                quote!(<#ty as #private::OrderedBytes>::read_ordered(&data[offset..end])?)
+            };
+
+            quote! {
+                let end = offset + <#ty as #private::OrderedBytes>::SIZE;
+                let #binding = #read;
+                let offset = end;
+            }
+        },
+    );
+
+    let bindings = fields.iter().map(|Field { binding, .. }| binding);
+    let construct = match &data.fields {
+        Fields::Named(_) => {
+            let members = fields.iter().map(|Field { member, .. }| member);
+            quote!(Self { #(#members: #bindings),* })
+        }
+        _ => quote!(Self(#(#bindings),*)),
+    };
+
+    Ok(quote! {
+        impl ::ore_rs::OrePlaintext for #name {
+            type Encoded = ::ore_rs::PlainText<{ #(#sizes)+* }>;
+
+            fn encode(&self) -> Self::Encoded {
+                #private::encode_with(|out| {
+                    let offset = 0;
+                    #(#writes)*
+                    let _ = offset;
+                })
+            }
+
+            fn decode(data: &Self::Encoded) -> ::core::result::Result<Self, ::ore_rs::OreError> {
+                let offset = 0;
+                #(#reads)*
+                let _ = offset;
+                ::core::result::Result::Ok(#construct)
+            }
+        }
+    })
+}
+
+/* Parse the `#[ore(...)]` attributes of a field */
+fn is_desc(field: &syn::Field) -> Result<bool, Error> {
+    let mut desc = false;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("ore"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("desc") {
+                desc = true;
+                Ok(())
+            } else if meta.path.is_ident("asc") {
+                desc = false;
+                Ok(())
+            } else {
+                Err(meta.error("expected `asc` or `desc`"))
+            }
+        })?;
+    }
+
+    Ok(desc)
+}
@@ -0,0 +1,245 @@
+use hex_literal::hex;
+use ore_rs::{
+    scheme::bit2::OreAes128ChaCha20, CipherText, Descending, FixedPoint, OreCipher, OreEncrypt,
+    OreError, OreOutput, OrePlaintext, PlainText,
+};
+use quickcheck::quickcheck;
+use std::cmp::Ordering;
+
+type Ore = OreAes128ChaCha20;
+
+fn init_ore() -> Ore {
+    let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+    let k2: [u8; 16] = hex!("d0d007a5 3f9a6848 83bc1f21 0f6595a3");
+
+    OreCipher::init(&k1, &k2).unwrap()
+}
+
+#[derive(Debug, PartialEq, OreEncrypt)]
+struct Event {
+    tenant_id: u32,
+    created_at: i64,
+}
+
+#[derive(Debug, PartialEq, OreEncrypt)]
+struct Score {
+    #[ore(desc)]
+    points: u64,
+    name: (u32, f32),
+}
+
+#[derive(Debug, PartialEq, OreEncrypt)]
+struct Wrapped {
+    tenant_id: u32,
+    created_at: Descending<i64>,
+}
+
+#[derive(Debug, PartialEq, OreEncrypt)]
+struct Attributed {
+    tenant_id: u32,
+    #[ore(desc)]
+    created_at: i64,
+}
+
+#[derive(Debug, PartialEq, OreEncrypt)]
+struct Nullable {
+    tenant_id: u32,
+    deleted_at: Option<i64>,
+}
+
+#[derive(Debug, PartialEq, OreEncrypt)]
+struct Nested(i8, #[ore(desc)] Event);
+
+/* A type with a hand written encoding */
+#[derive(Debug, PartialEq, PartialOrd)]
+struct Version {
+    major: u8,
+    minor: u8,
+}
+
+impl OrePlaintext for Version {
+    type Encoded = PlainText<2>;
+
+    fn encode(&self) -> PlainText<2> {
+        [self.major, self.minor]
+    }
+
+    fn decode(input: &PlainText<2>) -> Result<Self, OreError> {
+        Ok(Version {
+            major: input[0],
+            minor: input[1],
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, OreEncrypt)]
+struct Release {
+    version: Version,
+    #[ore(desc)]
+    build: u32,
+}
+
+#[derive(Debug, PartialEq, OreEncrypt)]
+struct Listing {
+    price: Option<FixedPoint<2>>,
+    version: Option<Version>,
+}
+
+/* The generated code must not pick up a `Result` alias from the caller's scope */
+mod shadowed {
+    #![allow(dead_code)]
+
+    use ore_rs::OreEncrypt;
+
+    pub type Result<T> = std::result::Result<T, ()>;
+
+    #[derive(Debug, PartialEq, OreEncrypt)]
+    pub struct Shadowed {
+        pub id: u32,
+        #[ore(desc)]
+        pub rank: i16,
+    }
+}
+
+quickcheck! {
+    fn fields_order_in_declaration_order(x: (u32, i64), y: (u32, i64)) -> bool {
+        let ore = init_ore();
+        let a = Event { tenant_id: x.0, created_at: x.1 }.encrypt(&ore).unwrap();
+        let b = Event { tenant_id: y.0, created_at: y.1 }.encrypt(&ore).unwrap();
+
+        match x.cmp(&y) {
+            Ordering::Greater => a > b,
+            Ordering::Less => a < b,
+            Ordering::Equal => a == b,
+        }
+    }
+
+    fn desc_field_reverses_order(x: u64, y: u64) -> bool {
+        let ore = init_ore();
+        let a = Score { points: x, name: (1, 0.5) }.encrypt_left(&ore).unwrap();
+        let b = Score { points: y, name: (1, 0.5) }.encrypt(&ore).unwrap();
+
+        match y.cmp(&x) {
+            Ordering::Greater => a > b,
+            Ordering::Less => a < b,
+            Ordering::Equal => a == b,
+        }
+    }
+
+    fn nullable_field(x: Option<i64>, y: Option<i64>) -> bool {
+        let ore = init_ore();
+        let a = Nullable { tenant_id: 1, deleted_at: x }.encrypt(&ore).unwrap();
+        let b = Nullable { tenant_id: 1, deleted_at: y }.encrypt(&ore).unwrap();
+
+        Nullable::decrypt(&a, &ore).unwrap().deleted_at == x && a.cmp(&b) == x.cmp(&y)
+    }
+
+    fn custom_plaintext_field(x: (u8, u8, u32), y: (u8, u8, u32)) -> bool {
+        let ore = init_ore();
+        let release = |(major, minor, build)| Release { version: Version { major, minor }, build };
+        let a = release(x).encrypt(&ore).unwrap();
+        let b = release(y).encrypt(&ore).unwrap();
+
+        let expected = (x.0, x.1, y.2).cmp(&(y.0, y.1, x.2));
+        Release::decrypt(&a, &ore).unwrap() == release(x) && a.cmp(&b) == expected
+    }
+
+    fn nullable_custom_fields(x: (Option<i128>, Option<u8>), y: (Option<i128>, Option<u8>)) -> bool {
+        let ore = init_ore();
+        let listing = |(price, major): (Option<i128>, Option<u8>)| Listing {
+            price: price.map(FixedPoint),
+            version: major.map(|major| Version { major, minor: 0 }),
+        };
+        let a = listing(x).encrypt(&ore).unwrap();
+        let b = listing(y).encrypt(&ore).unwrap();
+
+        Listing::decrypt(&a, &ore).unwrap() == listing(x) && a.cmp(&b) == x.cmp(&y)
+    }
+
+    fn decrypt_roundtrip(x: i8, tenant_id: u32, created_at: i64) -> bool {
+        let ore = init_ore();
+        let value = Nested(x, Event { tenant_id, created_at });
+        let ct = value.encrypt(&ore).unwrap();
+
+        Nested::decrypt(&ct, &ore).unwrap() == value
+    }
+}
+
+#[test]
+fn size_is_sum_of_fields() {
+    let ore = init_ore();
+    let ct = Nested(
+        1,
+        Event {
+            tenant_id: 2,
+            created_at: 3,
+        },
+    )
+    .encrypt(&ore)
+    .unwrap();
+
+    assert_eq!(ct.to_bytes().len(), CipherText::<Ore, 13>::size());
+}
+
+#[test]
+fn nested_desc_struct() {
+    let ore = init_ore();
+    let a = Nested(
+        0,
+        Event {
+            tenant_id: 1,
+            created_at: 5,
+        },
+    )
+    .encrypt(&ore)
+    .unwrap();
+    let b = Nested(
+        0,
+        Event {
+            tenant_id: 1,
+            created_at: 4,
+        },
+    )
+    .encrypt(&ore)
+    .unwrap();
+    let c = Nested(
+        1,
+        Event {
+            tenant_id: 9,
+            created_at: 9,
+        },
+    )
+    .encrypt(&ore)
+    .unwrap();
+
+    assert!(a < b);
+    assert!(b < c);
+}
+
+#[test]
+fn descending_field_matches_desc_attribute() {
+    let ore = init_ore();
+    let a = Wrapped {
+        tenant_id: 1,
+        created_at: Descending(-5),
+    }
+    .encrypt_left(&ore)
+    .unwrap();
+    let b = Attributed {
+        tenant_id: 1,
+        created_at: -5,
+    }
+    .encrypt_left(&ore)
+    .unwrap();
+
+    assert_eq!(ore.decrypt_left(&a).unwrap(), ore.decrypt_left(&b).unwrap());
+}
+
+#[test]
+fn shadowed_result_alias() {
+    let ore = init_ore();
+    let value = shadowed::Shadowed { id: 7, rank: -3 };
+    let ct = value.encrypt(&ore).unwrap();
+
+    assert_eq!(shadowed::Shadowed::decrypt(&ct, &ore).unwrap(), value);
+}
@@ -1,7 +1,19 @@
 use thiserror::Error;
 
 use crate::primitives::NONCE_SIZE;
-pub use crate::OreCipher;
+pub use crate::{OreCipher, OreCompare, VarOreCipher};
+use std::cmp::Ordering;
+
+mod envelope;
+#[cfg(feature = "serde")]
+mod serialize;
+mod view;
+
+pub use self::envelope::{
+    CipherTextKind, EnvelopeHeader, OreEnvelope, OreEnvelopeScheme, ENVELOPE_MAGIC,
+    ENVELOPE_VERSION,
+};
+pub use self::view::{CipherTextRef, LeftRef};
 
 /// The trait of any encryption output (either Left, Right or combined).
 pub trait OreOutput: Sized {
@@ -41,6 +53,34 @@ pub struct CipherText<S: OreCipher, const N: usize> {
     pub right: Right<S, N>,
 }
 
+/// A [`Left`] ciphertext for a plaintext whose length is only known at runtime (e.g. a string).
+#[derive(Debug, Clone)]
+pub struct VarLeft<S: VarOreCipher> {
+    /* One Left block per plaintext byte */
+    pub f: Vec<S::LeftBlockType>,
+
+    /* Transformed input, one byte per plaintext byte */
+    pub xt: Vec<u8>,
+}
+
+/// A [`Right`] ciphertext for a plaintext whose length is only known at runtime.
+#[derive(Debug, Clone)]
+pub struct VarRight<S: VarOreCipher> {
+    pub nonce: [u8; NONCE_SIZE],
+    pub data: Vec<S::RightBlockType>,
+}
+
+/// A [`CipherText`] for a plaintext whose length is only known at runtime.
+///
+/// Ciphertexts of different lengths can be compared and are ordered lexicographically, i.e. a
+/// plaintext orders before every longer plaintext that it is a prefix of.
+/// Note that the length of the plaintext is not hidden.
+#[derive(Debug, Clone)]
+pub struct VarCipherText<S: VarOreCipher> {
+    pub left: VarLeft<S>,
+    pub right: VarRight<S>,
+}
+
 pub trait CipherTextBlock: Default + Copy + std::fmt::Debug {
     const BLOCK_SIZE: usize;
 
@@ -51,9 +91,46 @@ pub trait CipherTextBlock: Default + Copy + std::fmt::Debug {
     fn default_in_place(&mut self);
 }
 
-#[derive(Debug, Error)]
-#[error("Unable to parse ORE Ciphertext")]
-pub struct ParseError;
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+#[error("Unable to parse ORE Ciphertext: {reason}")]
+pub struct ParseError {
+    reason: ParseErrorReason,
+}
+
+/// Why a ciphertext couldn't be parsed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ParseErrorReason {
+    #[error("expected {expected} bytes but got {actual}")]
+    WrongLength { expected: usize, actual: usize },
+    #[error("expected {expected} bytes but got {actual} (trailing bytes)")]
+    TrailingBytes { expected: usize, actual: usize },
+    #[error("invalid envelope header")]
+    BadHeader,
+    #[error("invalid block")]
+    InvalidBlock,
+}
+
+impl ParseError {
+    pub fn reason(&self) -> ParseErrorReason {
+        self.reason
+    }
+
+    /* Check that the input is exactly the expected number of bytes */
+    pub(crate) fn check_length(expected: usize, actual: usize) -> Result<(), Self> {
+        match actual.cmp(&expected) {
+            Ordering::Equal => Ok(()),
+            Ordering::Less => Err(ParseErrorReason::WrongLength { expected, actual }.into()),
+            Ordering::Greater => Err(ParseErrorReason::TrailingBytes { expected, actual }.into()),
+        }
+    }
+}
+
+impl From<ParseErrorReason> for ParseError {
+    fn from(reason: ParseErrorReason) -> Self {
+        Self { reason }
+    }
+}
 
 impl<S: OreCipher, const N: usize> Left<S, N> {
     pub(crate) fn init() -> Self {
@@ -79,6 +156,8 @@ impl<S: OreCipher, const N: usize> OreOutput for Left<S, N> {
     }
 
     fn from_slice(data: &[u8]) -> Result<Self, ParseError> {
+        ParseError::check_length(Self::size(), data.len())?;
+
         let mut out = Self::init();
         out.xt.copy_from_slice(&data[0..N]);
         for i in 0..N {
@@ -116,6 +195,8 @@ impl<S: OreCipher, const N: usize> OreOutput for Right<S, N> {
     }
 
     fn from_slice(data: &[u8]) -> Result<Self, ParseError> {
+        ParseError::check_length(Self::size(), data.len())?;
+
         let mut out = Self::init();
         out.nonce.copy_from_slice(&data[0..NONCE_SIZE]);
         for i in 0..N {
@@ -140,9 +221,7 @@ impl<S: OreCipher, const N: usize> OreOutput for CipherText<S, N> {
 
     /// Deserialize from a slice of bytes
     fn from_slice(data: &[u8]) -> Result<Self, ParseError> {
-        if data.len() != (Left::<S, N>::size() + Right::<S, N>::size()) {
-            return Err(ParseError);
-        }
+        ParseError::check_length(Self::size(), data.len())?;
         let (left, right) = data.split_at(Left::<S, N>::size());
         let left = Left::<S, N>::from_slice(left)?;
         let right = Right::<S, N>::from_slice(right)?;
@@ -150,3 +229,362 @@ impl<S: OreCipher, const N: usize> OreOutput for CipherText<S, N> {
         Ok(Self { left, right })
     }
 }
+
+impl<S: VarOreCipher> VarLeft<S> {
+    pub(crate) fn init(len: usize) -> Self {
+        Self {
+            xt: vec![0; len],
+            f: vec![S::LeftBlockType::default(); len],
+        }
+    }
+
+    /// The number of blocks (plaintext bytes) in the ciphertext
+    pub fn len(&self) -> usize {
+        self.xt.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.xt.is_empty()
+    }
+
+    /// The size (in bytes) of this encrypted value
+    pub fn size(&self) -> usize {
+        self.len() * (S::LeftBlockType::BLOCK_SIZE + 1)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut vec = Vec::with_capacity(self.size());
+        vec.extend_from_slice(&self.xt);
+        self.f
+            .iter()
+            .for_each(|&block| vec.append(&mut block.to_bytes()));
+
+        vec
+    }
+
+    /// Deserialize from a slice of bytes. The number of blocks is derived from its length.
+    pub fn from_slice(data: &[u8]) -> Result<Self, ParseError> {
+        let block_size = S::LeftBlockType::BLOCK_SIZE + 1;
+        let len = data.len() / block_size;
+        ParseError::check_length(len * block_size, data.len())?;
+
+        let (xt, f) = data.split_at(len);
+        Ok(Self {
+            xt: xt.to_vec(),
+            f: f.chunks(S::LeftBlockType::BLOCK_SIZE)
+                .map(S::LeftBlockType::from_bytes)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl<S: VarOreCipher> VarRight<S> {
+    pub(crate) fn init(len: usize) -> Self {
+        Self {
+            nonce: Default::default(),
+            data: vec![Default::default(); len],
+        }
+    }
+
+    /// The number of blocks (plaintext bytes) in the ciphertext
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The size (in bytes) of this encrypted value
+    pub fn size(&self) -> usize {
+        (self.len() * S::RightBlockType::BLOCK_SIZE) + NONCE_SIZE
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut vec = Vec::with_capacity(self.size());
+        vec.extend_from_slice(&self.nonce);
+        self.data
+            .iter()
+            .for_each(|&block| vec.append(&mut block.to_bytes()));
+
+        vec
+    }
+
+    /// Deserialize from a slice of bytes. The number of blocks is derived from its length.
+    pub fn from_slice(data: &[u8]) -> Result<Self, ParseError> {
+        if data.len() < NONCE_SIZE {
+            return Err(ParseErrorReason::WrongLength {
+                expected: NONCE_SIZE,
+                actual: data.len(),
+            }
+            .into());
+        }
+
+        let (nonce, blocks) = data.split_at(NONCE_SIZE);
+        let len = blocks.len() / S::RightBlockType::BLOCK_SIZE;
+        ParseError::check_length(NONCE_SIZE + len * S::RightBlockType::BLOCK_SIZE, data.len())?;
+
+        let mut out = Self::init(0);
+        out.nonce.copy_from_slice(nonce);
+        out.data = blocks
+            .chunks(S::RightBlockType::BLOCK_SIZE)
+            .map(S::RightBlockType::from_bytes)
+            .collect::<Result<_, _>>()?;
+
+        Ok(out)
+    }
+}
+
+impl<S: VarOreCipher> VarCipherText<S> {
+    /// The number of blocks (plaintext bytes) in the ciphertext
+    pub fn len(&self) -> usize {
+        self.left.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty()
+    }
+
+    /// The size (in bytes) of this encrypted value
+    pub fn size(&self) -> usize {
+        self.left.size() + self.right.size()
+    }
+
+    /// Serialize the ciphertext into a vector of bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [self.left.to_bytes(), self.right.to_bytes()].concat()
+    }
+
+    /// Deserialize from a slice of bytes. The number of blocks is derived from its length.
+    pub fn from_slice(data: &[u8]) -> Result<Self, ParseError> {
+        let block_size = S::LeftBlockType::BLOCK_SIZE + 1 + S::RightBlockType::BLOCK_SIZE;
+        if data.len() < NONCE_SIZE {
+            return Err(ParseErrorReason::WrongLength {
+                expected: NONCE_SIZE,
+                actual: data.len(),
+            }
+            .into());
+        }
+
+        let len = (data.len() - NONCE_SIZE) / block_size;
+        ParseError::check_length(NONCE_SIZE + len * block_size, data.len())?;
+
+        let (left, right) = data.split_at(len * (S::LeftBlockType::BLOCK_SIZE + 1));
+        Ok(Self {
+            left: VarLeft::from_slice(left)?,
+            right: VarRight::from_slice(right)?,
+        })
+    }
+}
+
+impl<S: OreCompare, const N: usize> PartialEq for CipherText<S, N> {
+    fn eq(&self, b: &Self) -> bool {
+        matches!(self.cmp(b), Ordering::Equal)
+    }
+}
+
+impl<S: OreCompare, const N: usize> Ord for CipherText<S, N> {
+    fn cmp(&self, b: &Self) -> Ordering {
+        S::compare_left(&self.left, b)
+    }
+}
+
+impl<S: OreCompare, const N: usize> PartialOrd for CipherText<S, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/*
+ * (From the Rust docs)
+ * This property cannot be checked by the compiler, and therefore Eq implies PartialEq, and has no extra methods.
+ */
+impl<S: OreCompare, const N: usize> Eq for CipherText<S, N> {}
+
+impl<S: OreCompare, const N: usize> PartialEq<CipherText<S, N>> for Left<S, N> {
+    fn eq(&self, b: &CipherText<S, N>) -> bool {
+        matches!(S::compare_left(self, b), Ordering::Equal)
+    }
+}
+
+impl<S: OreCompare, const N: usize> PartialOrd<CipherText<S, N>> for Left<S, N> {
+    fn partial_cmp(&self, b: &CipherText<S, N>) -> Option<Ordering> {
+        Some(S::compare_left(self, b))
+    }
+}
+
+impl<S: VarOreCipher> PartialEq for VarCipherText<S> {
+    fn eq(&self, b: &Self) -> bool {
+        matches!(self.cmp(b), Ordering::Equal)
+    }
+}
+
+impl<S: VarOreCipher> Ord for VarCipherText<S> {
+    fn cmp(&self, b: &Self) -> Ordering {
+        S::compare_left_var(&self.left, b)
+    }
+}
+
+impl<S: VarOreCipher> PartialOrd for VarCipherText<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: VarOreCipher> Eq for VarCipherText<S> {}
+
+impl<S: VarOreCipher> PartialEq<VarCipherText<S>> for VarLeft<S> {
+    fn eq(&self, b: &VarCipherText<S>) -> bool {
+        matches!(S::compare_left_var(self, b), Ordering::Equal)
+    }
+}
+
+impl<S: VarOreCipher> PartialOrd<VarCipherText<S>> for VarLeft<S> {
+    fn partial_cmp(&self, b: &VarCipherText<S>) -> Option<Ordering> {
+        Some(S::compare_left_var(self, b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::{bit16, bit2, bit3, bit4};
+    use crate::test_utils::init_ore;
+    use crate::OreEncrypt;
+
+    /* Repeat the input to the given length so that random data reaches the block parsers */
+    fn fill(data: &[u8], len: usize) -> Vec<u8> {
+        data.iter().copied().cycle().take(len).collect()
+    }
+
+    /* Feed the input (as is and resized to the expected length) to every parser for a scheme */
+    fn parse_all<S: OreEnvelopeScheme>(data: &[u8]) {
+        macro_rules! parse_fixed {
+            ($($n:literal),*) => {
+                $(
+                    for input in [data.to_vec(), fill(data, CipherText::<S, $n>::size())] {
+                        let _ = Left::<S, $n>::from_slice(&input);
+                        let _ = Right::<S, $n>::from_slice(&input);
+                        let _ = CipherText::<S, $n>::from_slice(&input);
+                        let _ = CipherText::<S, $n>::from_envelope(&input);
+                        let reversed: Vec<u8> = input.iter().rev().copied().collect();
+                        let _ = S::compare_raw_slices(&input, &reversed);
+                        let _ = S::compare_left_raw_slices(&input[..input.len().min(Left::<S, $n>::size())], &reversed);
+                    }
+                    let _ = Left::<S, $n>::from_slice(&fill(data, Left::<S, $n>::size()));
+                    let _ = Right::<S, $n>::from_slice(&fill(data, Right::<S, $n>::size()));
+                )*
+            }
+        }
+
+        parse_fixed!(1, 4, 8, 16);
+        let _ = EnvelopeHeader::parse(data);
+        let _ = S::compare_envelopes(data, data);
+    }
+
+    /* Feed the input to every variable length parser for a scheme */
+    fn parse_var<S: VarOreCipher>(data: &[u8]) {
+        let _ = VarLeft::<S>::from_slice(data);
+        let _ = VarRight::<S>::from_slice(data);
+        let _ = VarCipherText::<S>::from_slice(data);
+        let reversed: Vec<u8> = data.iter().rev().copied().collect();
+        let _ = S::compare_var_raw_slices(data, &reversed);
+        let _ = S::compare_left_var_raw_slices(data, &reversed);
+    }
+
+    quickcheck! {
+        fn parse_arbitrary_bytes(data: Vec<u8>) -> bool {
+            parse_all::<bit2::OreAes128ChaCha20>(&data);
+            parse_all::<bit3::OreAes128ChaCha20>(&data);
+            parse_all::<bit4::OreAes128ChaCha20>(&data);
+            parse_all::<bit16::OreAes128ChaCha20>(&data);
+            parse_var::<bit2::OreAes128ChaCha20>(&data);
+
+            true
+        }
+
+        fn parse_truncated(x: u64, len: usize) -> bool {
+            let ore: bit2::OreAes128ChaCha20 = init_ore();
+            let bytes = x.encrypt(&ore).unwrap().to_bytes();
+            let len = len % bytes.len();
+
+            CipherText::<bit2::OreAes128ChaCha20, 8>::from_slice(&bytes[..len])
+                .unwrap_err()
+                .reason()
+                == ParseErrorReason::WrongLength { expected: bytes.len(), actual: len }
+        }
+
+        fn parse_trailing_bytes(x: u64, extra: Vec<u8>) -> bool {
+            let ore: bit2::OreAes128ChaCha20 = init_ore();
+            let bytes = x.encrypt(&ore).unwrap().to_bytes();
+            let expected = bytes.len();
+            let input = [bytes, vec![0], extra].concat();
+
+            CipherText::<bit2::OreAes128ChaCha20, 8>::from_slice(&input)
+                .unwrap_err()
+                .reason()
+                == ParseErrorReason::TrailingBytes { expected, actual: input.len() }
+        }
+
+        fn parse_corrupted_envelope(x: u32, index: usize, byte: u8) -> bool {
+            let ore: bit3::OreAes128ChaCha20 = init_ore();
+            let ct = x.encrypt(&ore).unwrap();
+            let mut bytes = ct.to_envelope();
+            let index = index % bytes.len();
+            bytes[index] = byte;
+
+            match CipherText::<bit3::OreAes128ChaCha20, 4>::from_envelope(&bytes) {
+                Ok(parsed) => index >= EnvelopeHeader::SIZE || parsed == ct,
+                Err(_) => true,
+            }
+        }
+    }
+
+    #[test]
+    fn parse_error_reasons() {
+        type Ore = bit3::OreAes128ChaCha20;
+        let ore: Ore = init_ore();
+        let ct = 42u32.encrypt(&ore).unwrap();
+        let left = ct.left.to_bytes();
+        let right = ct.right.to_bytes();
+
+        assert_eq!(
+            Left::<Ore, 4>::from_slice(&left[1..]).unwrap_err().reason(),
+            ParseErrorReason::WrongLength {
+                expected: left.len(),
+                actual: left.len() - 1
+            }
+        );
+        assert_eq!(
+            Right::<Ore, 4>::from_slice(&[&right[..], &[0]].concat())
+                .unwrap_err()
+                .reason(),
+            ParseErrorReason::TrailingBytes {
+                expected: right.len(),
+                actual: right.len() + 1
+            }
+        );
+        assert_eq!(
+            Right::<Ore, 4>::from_slice(&vec![255; right.len()])
+                .unwrap_err()
+                .reason(),
+            ParseErrorReason::InvalidBlock
+        );
+        assert_eq!(
+            CipherText::<Ore, 4>::from_envelope(&[b"XX", &ct.to_envelope()[2..]].concat())
+                .unwrap_err()
+                .reason(),
+            ParseErrorReason::BadHeader
+        );
+        assert_eq!(
+            CipherText::<Ore, 8>::from_envelope(&ct.to_envelope())
+                .unwrap_err()
+                .reason(),
+            ParseErrorReason::BadHeader
+        );
+        assert_eq!(
+            ParseError::from(ParseErrorReason::BadHeader).to_string(),
+            "Unable to parse ORE Ciphertext: invalid envelope header"
+        );
+    }
+}
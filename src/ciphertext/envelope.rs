@@ -0,0 +1,321 @@
+/*
+ * A self-describing envelope for fixed size ciphertexts.
+ *
+ * The envelope prefixes the bytes produced by OreOutput::to_bytes with a header:
+ *
+ *   magic (2 bytes) || version (1 byte) || scheme id (1 byte) || kind (1 byte) || blocks (4 bytes, BE)
+ *
+ * so that a stored ciphertext says which scheme, block count and format version produced it.
+ * Parsers check the header against the type they parse into and reject mismatched data rather
+ * than misinterpreting it. Future format changes must bump the version.
+ */
+
+use super::*;
+use std::convert::TryFrom;
+
+/// The bytes at the start of every ciphertext envelope
+pub const ENVELOPE_MAGIC: [u8; 2] = *b"OR";
+
+/// The envelope format version written by this release
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Which part of a ciphertext an envelope holds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CipherTextKind {
+    Left = 1,
+    Right = 2,
+    Full = 3,
+}
+
+impl CipherTextKind {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Self::Left),
+            2 => Some(Self::Right),
+            3 => Some(Self::Full),
+            _ => None,
+        }
+    }
+}
+
+/// The header of a ciphertext envelope.
+///
+/// Parsing a header doesn't require knowing the ciphertext type up front so it can be used to
+/// inspect stored data (e.g. to pick the type to parse it into).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EnvelopeHeader {
+    pub version: u8,
+    pub scheme_id: u8,
+    pub kind: CipherTextKind,
+    pub blocks: u32,
+}
+
+impl EnvelopeHeader {
+    /// The size (in bytes) of an encoded header
+    pub const SIZE: usize = 9;
+
+    /// The header for a ciphertext of the given kind and block count from scheme `S`.
+    pub fn new<S: OreEnvelopeScheme>(kind: CipherTextKind, blocks: u32) -> Self {
+        Self {
+            version: ENVELOPE_VERSION,
+            scheme_id: S::SCHEME_ID,
+            kind,
+            blocks,
+        }
+    }
+
+    /// Split an envelope into its header and the ciphertext bytes that follow it.
+    ///
+    /// Returns a [`ParseError`] if the data is too short or if the magic bytes, version or kind
+    /// aren't recognised.
+    pub fn parse(data: &[u8]) -> Result<(Self, &[u8]), ParseError> {
+        if data.len() < Self::SIZE {
+            return Err(ParseErrorReason::WrongLength {
+                expected: Self::SIZE,
+                actual: data.len(),
+            }
+            .into());
+        }
+
+        let (header, body) = data.split_at(Self::SIZE);
+        if header[0..2] != ENVELOPE_MAGIC || header[2] != ENVELOPE_VERSION {
+            return Err(ParseErrorReason::BadHeader.into());
+        }
+
+        let header = Self {
+            version: header[2],
+            scheme_id: header[3],
+            kind: CipherTextKind::from_byte(header[4]).ok_or(ParseErrorReason::BadHeader)?,
+            blocks: u32::from_be_bytes([header[5], header[6], header[7], header[8]]),
+        };
+
+        Ok((header, body))
+    }
+
+    /// Encode the header
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut out = [0; Self::SIZE];
+        out[0..2].copy_from_slice(&ENVELOPE_MAGIC);
+        out[2] = self.version;
+        out[3] = self.scheme_id;
+        out[4] = self.kind as u8;
+        out[5..9].copy_from_slice(&self.blocks.to_be_bytes());
+        out
+    }
+
+    /*
+     * The size of the ciphertext following this header if it was produced by scheme S.
+     * The block count comes from untrusted input so this is None if the size overflows.
+     */
+    pub(crate) fn body_size<S: OreCipher>(&self) -> Option<usize> {
+        let blocks = usize::try_from(self.blocks).ok()?;
+        let left = blocks.checked_mul(S::LeftBlockType::BLOCK_SIZE + 1)?;
+        let right = blocks
+            .checked_mul(S::RightBlockType::BLOCK_SIZE)?
+            .checked_add(NONCE_SIZE)?;
+
+        match self.kind {
+            CipherTextKind::Left => Some(left),
+            CipherTextKind::Right => Some(right),
+            CipherTextKind::Full => left.checked_add(right),
+        }
+    }
+}
+
+/// A scheme whose ciphertexts can be wrapped in an envelope (see [`OreEnvelope`]).
+pub trait OreEnvelopeScheme: OreCipher {
+    /// Identifies the scheme in a ciphertext envelope. Every scheme must use a different value.
+    const SCHEME_ID: u8;
+
+    /// Compare two ciphertext envelopes. The first can be a [`Left`] or a full [`CipherText`] and
+    /// the second must be a full [`CipherText`].
+    ///
+    /// Unlike [`OreCipher::compare_raw_slices`] the number of blocks is read from the headers.
+    /// Returns `None` if either envelope is invalid, is from another scheme or if the block counts
+    /// don't match.
+    fn compare_envelopes(a: &[u8], b: &[u8]) -> Option<Ordering> {
+        let (a_header, a) = EnvelopeHeader::parse(a).ok()?;
+        let (b_header, b) = EnvelopeHeader::parse(b).ok()?;
+
+        if a_header.scheme_id != Self::SCHEME_ID
+            || b_header.scheme_id != Self::SCHEME_ID
+            || a_header.blocks != b_header.blocks
+            || b_header.kind != CipherTextKind::Full
+            || Some(a.len()) != a_header.body_size::<Self>()
+            || Some(b.len()) != b_header.body_size::<Self>()
+        {
+            return None;
+        }
+
+        match a_header.kind {
+            CipherTextKind::Full => Self::compare_raw_slices(a, b),
+            CipherTextKind::Left => Self::compare_left_raw_slices(a, b),
+            CipherTextKind::Right => None,
+        }
+    }
+}
+
+/// Fixed size ciphertexts that can be wrapped in a self-describing envelope.
+///
+/// ```rust
+/// # use ore_rs::{CipherText, OreCipher, OreEncrypt, OreEnvelope, scheme::{bit2, bit3}};
+/// # use hex_literal::hex;
+/// # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let ore: bit2::OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+/// let bytes = 456u64.encrypt(&ore).unwrap().to_envelope();
+///
+/// assert!(CipherText::<bit2::OreAes128ChaCha20, 8>::from_envelope(&bytes).is_ok());
+/// /* The wrong scheme or number of blocks is rejected */
+/// assert!(CipherText::<bit3::OreAes128ChaCha20, 8>::from_envelope(&bytes).is_err());
+/// assert!(CipherText::<bit2::OreAes128ChaCha20, 4>::from_envelope(&bytes).is_err());
+/// ```
+pub trait OreEnvelope: OreOutput {
+    /// The header written for (and expected from) this type
+    fn header() -> EnvelopeHeader;
+
+    /// Serialize into an envelope
+    fn to_envelope(&self) -> Vec<u8> {
+        [&Self::header().to_bytes()[..], &self.to_bytes()].concat()
+    }
+
+    /// Deserialize from an envelope, checking that its header matches this type
+    fn from_envelope(data: &[u8]) -> Result<Self, ParseError> {
+        let (header, body) = EnvelopeHeader::parse(data)?;
+        if header != Self::header() {
+            return Err(ParseErrorReason::BadHeader.into());
+        }
+
+        Self::from_slice(body)
+    }
+}
+
+macro_rules! impl_ore_envelope {
+    ($($t:ident => $kind:ident),*) => {
+        $(
+            impl<S: OreEnvelopeScheme, const N: usize> OreEnvelope for $t<S, N> {
+                fn header() -> EnvelopeHeader {
+                    EnvelopeHeader::new::<S>(CipherTextKind::$kind, N as u32)
+                }
+            }
+        )*
+    }
+}
+
+impl_ore_envelope!(Left => Left, Right => Right, CipherText => Full);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::{bit2, bit3, bit4};
+    use crate::test_utils::init_ore;
+    use crate::OreEncrypt;
+    use hex_literal::hex;
+
+    type Ore = bit2::OreAes128ChaCha20;
+
+    quickcheck! {
+        fn roundtrip(x: u64) -> bool {
+            let ore: Ore = init_ore();
+            let ct = x.encrypt(&ore).unwrap();
+            let left = CipherText::<Ore, 8>::from_envelope(&ct.to_envelope()).unwrap().left;
+            let right = Right::<Ore, 8>::from_envelope(&ct.right.to_envelope()).unwrap();
+
+            Left::<Ore, 8>::from_envelope(&ct.left.to_envelope()).unwrap() == ct
+                && CipherText { left, right } == ct
+        }
+
+        fn compare_envelopes(x: u32, y: u32) -> bool {
+            let ore: Ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap().to_envelope();
+
+            Ore::compare_envelopes(&a.to_envelope(), &b) == Some(x.cmp(&y))
+                && Ore::compare_envelopes(&a.left.to_envelope(), &b) == Some(x.cmp(&y))
+        }
+
+        fn compare_envelopes_bit4(x: u64, y: u64) -> bool {
+            let ore: bit4::OreAes128ChaCha20 = init_ore();
+            let a = x.encrypt(&ore).unwrap().left.to_envelope();
+            let b = y.encrypt(&ore).unwrap().to_envelope();
+
+            bit4::OreAes128ChaCha20::compare_envelopes(&a, &b) == Some(x.cmp(&y))
+        }
+    }
+
+    #[test]
+    fn header_layout() {
+        let ore: Ore = init_ore();
+        let bytes = 7u32.encrypt(&ore).unwrap().to_envelope();
+
+        assert_eq!(bytes[0..9], hex!("4f52 01 02 03 00000004"));
+        assert_eq!(
+            bytes.len(),
+            EnvelopeHeader::SIZE + CipherText::<Ore, 4>::size()
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_envelopes() {
+        let ore: Ore = init_ore();
+        let ct = 7u32.encrypt(&ore).unwrap();
+        let bytes = ct.to_envelope();
+
+        /* Wrong scheme, block count and kind */
+        assert!(CipherText::<bit3::OreAes128ChaCha20, 4>::from_envelope(&bytes).is_err());
+        assert!(CipherText::<Ore, 8>::from_envelope(&bytes).is_err());
+        assert!(Left::<Ore, 4>::from_envelope(&bytes).is_err());
+
+        /* No header, bad magic, unknown version and truncated data */
+        assert!(CipherText::<Ore, 4>::from_envelope(&ct.to_bytes()).is_err());
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(CipherText::<Ore, 4>::from_envelope(&bad_magic).is_err());
+        let mut future_version = bytes.clone();
+        future_version[2] = ENVELOPE_VERSION + 1;
+        assert!(CipherText::<Ore, 4>::from_envelope(&future_version).is_err());
+        assert!(CipherText::<Ore, 4>::from_envelope(&bytes[..bytes.len() - 1]).is_err());
+        assert!(EnvelopeHeader::parse(&bytes[..4]).is_err());
+    }
+
+    #[test]
+    fn compare_envelopes_rejects_mismatches() {
+        let ore: Ore = init_ore();
+        let a = 7u32.encrypt(&ore).unwrap();
+        let b = 8u64.encrypt(&ore).unwrap();
+
+        /* Different block counts, a right ciphertext and a raw ciphertext */
+        assert_eq!(
+            Ore::compare_envelopes(&a.to_envelope(), &b.to_envelope()),
+            None
+        );
+        assert_eq!(
+            Ore::compare_envelopes(&a.right.to_envelope(), &a.to_envelope()),
+            None
+        );
+        assert_eq!(
+            Ore::compare_envelopes(&a.to_envelope(), &a.to_bytes()),
+            None
+        );
+        assert_eq!(
+            bit3::OreAes128ChaCha20::compare_envelopes(&a.to_envelope(), &a.to_envelope()),
+            None
+        );
+    }
+
+    #[test]
+    fn huge_block_counts() {
+        let header = EnvelopeHeader::new::<Ore>(CipherTextKind::Full, u32::MAX);
+        let block_size = <Ore as OreCipher>::LeftBlockType::BLOCK_SIZE
+            + 1
+            + <Ore as OreCipher>::RightBlockType::BLOCK_SIZE;
+        let expected = u64::from(u32::MAX) * block_size as u64 + NONCE_SIZE as u64;
+
+        /* Overflows on 32 bit targets */
+        assert_eq!(header.body_size::<Ore>(), usize::try_from(expected).ok());
+
+        let mut a = header.to_bytes().to_vec();
+        a.extend_from_slice(&[0; 64]);
+        assert_eq!(Ore::compare_envelopes(&a, &a), None);
+    }
+}
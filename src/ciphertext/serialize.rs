@@ -0,0 +1,164 @@
+/*
+ * Serde support for fixed size ciphertexts.
+ *
+ * Ciphertexts are serialized using the same layout as OreOutput::to_bytes. Binary formats (e.g.
+ * bincode) store the raw bytes while human readable formats (e.g. JSON) store a hex string.
+ * Deserialization also accepts a sequence of bytes and checks the length against the expected size
+ * for the scheme and number of blocks before parsing.
+ */
+
+use super::*;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+fn serialize<T: OreOutput, Ser: Serializer>(
+    value: &T,
+    serializer: Ser,
+) -> Result<Ser::Ok, Ser::Error> {
+    let bytes = value.to_bytes();
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&hex::encode(bytes))
+    } else {
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+fn deserialize<'de, T: OreOutput, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(OreOutputVisitor(PhantomData))
+    } else {
+        deserializer.deserialize_bytes(OreOutputVisitor(PhantomData))
+    }
+}
+
+struct OreOutputVisitor<T>(PhantomData<T>);
+
+impl<'de, T: OreOutput> Visitor<'de> for OreOutputVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an ORE ciphertext of {} bytes", T::size())
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<T, E> {
+        if v.len() != T::size() {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+
+        T::from_slice(v).map_err(E::custom)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        let bytes = hex::decode(v).map_err(E::custom)?;
+        self.visit_bytes(&bytes)
+    }
+
+    /* Some formats store bytes as a sequence so accept that too (without reading past the size) */
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+        let mut bytes = Vec::with_capacity(T::size());
+        while let Some(byte) = seq.next_element()? {
+            if bytes.len() == T::size() {
+                return Err(de::Error::invalid_length(bytes.len() + 1, &self));
+            }
+            bytes.push(byte);
+        }
+
+        self.visit_bytes(&bytes)
+    }
+}
+
+macro_rules! impl_serde {
+    ($($t:ident),*) => {
+        $(
+            impl<S: OreCipher, const N: usize> Serialize for $t<S, N> {
+                fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+                    serialize(self, serializer)
+                }
+            }
+
+            impl<'de, S: OreCipher, const N: usize> Deserialize<'de> for $t<S, N> {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize(deserializer)
+                }
+            }
+        )*
+    }
+}
+
+impl_serde!(Left, Right, CipherText);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::bit2::OreAes128ChaCha20;
+    use crate::test_utils::init_ore;
+    use crate::OreEncrypt;
+    use serde::de::value::{Error as ValueError, SeqDeserializer};
+
+    type Ore = OreAes128ChaCha20;
+
+    /* Deserialize from a sequence of bytes */
+    fn from_seq<T: OreOutput>(bytes: Vec<u8>) -> Result<T, ValueError> {
+        deserialize(SeqDeserializer::new(bytes.into_iter()))
+    }
+
+    quickcheck! {
+        fn json_roundtrip(x: u64) -> bool {
+            let ore: Ore = init_ore();
+            let ct = x.encrypt(&ore).unwrap();
+            let json = serde_json::to_string(&ct).unwrap();
+            let parsed: CipherText<Ore, 8> = serde_json::from_str(&json).unwrap();
+
+            json == format!("\"{}\"", hex::encode(ct.to_bytes())) && parsed == ct
+        }
+
+        fn bincode_roundtrip(x: u64) -> bool {
+            let ore: Ore = init_ore();
+            let ct = x.encrypt(&ore).unwrap();
+            let bytes = bincode::serialize(&ct).unwrap();
+            let parsed: CipherText<Ore, 8> = bincode::deserialize(&bytes).unwrap();
+
+            bytes.ends_with(&ct.to_bytes()) && parsed == ct
+        }
+
+        fn left_and_right_roundtrip(x: u32, y: u32) -> bool {
+            let ore: Ore = init_ore();
+            let a = x.encrypt_left(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            let left: Left<Ore, 4> = serde_json::from_str(&serde_json::to_string(&a).unwrap()).unwrap();
+            let right: Right<Ore, 4> = bincode::deserialize(&bincode::serialize(&b.right).unwrap()).unwrap();
+            let ct = CipherText { left: b.left, right };
+
+            left.partial_cmp(&ct) == Some(x.cmp(&y))
+        }
+
+        fn seq_roundtrip(x: u64) -> bool {
+            let ore: Ore = init_ore();
+            let ct = x.encrypt(&ore).unwrap();
+            let parsed: CipherText<Ore, 8> = from_seq(ct.to_bytes()).unwrap();
+
+            parsed == ct
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_length() {
+        let ore: Ore = init_ore();
+        let ct = 42u32.encrypt(&ore).unwrap();
+        let json = serde_json::to_string(&ct).unwrap();
+        let bytes = bincode::serialize(&ct).unwrap();
+
+        assert!(serde_json::from_str::<CipherText<Ore, 8>>(&json).is_err());
+        assert!(bincode::deserialize::<CipherText<Ore, 8>>(&bytes).is_err());
+        assert!(serde_json::from_str::<Left<Ore, 4>>("\"00ff\"").is_err());
+        assert!(serde_json::from_str::<Right<Ore, 4>>("\"not hex\"").is_err());
+
+        let mut long = ct.to_bytes();
+        assert!(from_seq::<CipherText<Ore, 4>>(long[1..].to_vec()).is_err());
+        long.push(0);
+        assert!(from_seq::<CipherText<Ore, 4>>(long).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+/*
+ * Borrowed views of serialized ciphertexts.
+ *
+ * A view wraps a slice in the OreOutput::to_bytes layout (e.g. a page of a memory mapped index)
+ * which has been checked to have the right length and valid blocks. Views compare using the raw
+ * slice comparisons of the scheme so sorting and scanning doesn't allocate or copy any blocks.
+ */
+
+use super::*;
+use std::marker::PhantomData;
+use std::slice::ChunksExact;
+
+/// A borrowed, zero-copy view of a serialized [`Left`] ciphertext.
+#[derive(Debug)]
+pub struct LeftRef<'a, S: OreCipher, const N: usize> {
+    data: &'a [u8],
+    scheme: PhantomData<S>,
+}
+
+/// A borrowed, zero-copy view of a serialized [`CipherText`].
+///
+/// ```rust
+/// # use ore_rs::{CipherText, CipherTextRef, OreCipher, OreEncrypt, OreOutput, scheme::bit2::OreAes128ChaCha20};
+/// # use hex_literal::hex;
+/// # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+/// /* A page of stored ciphertexts */
+/// let page: Vec<u8> = [30u32, 10, 20]
+///     .iter()
+///     .flat_map(|x| x.encrypt(&ore).unwrap().to_bytes())
+///     .collect();
+///
+/// let mut views = page
+///     .chunks(CipherText::<OreAes128ChaCha20, 4>::size())
+///     .map(CipherTextRef::<OreAes128ChaCha20, 4>::new)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// views.sort();
+///
+/// let query = 20u32.encrypt_left(&ore).unwrap().to_bytes();
+/// let query = ore_rs::LeftRef::<OreAes128ChaCha20, 4>::new(&query).unwrap();
+/// assert!(query == views[1]);
+/// ```
+#[derive(Debug)]
+pub struct CipherTextRef<'a, S: OreCipher, const N: usize> {
+    data: &'a [u8],
+    scheme: PhantomData<S>,
+}
+
+/* Derive would require S: Clone */
+impl<'a, S: OreCipher, const N: usize> Clone for LeftRef<'a, S, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, S: OreCipher, const N: usize> Copy for LeftRef<'a, S, N> {}
+
+impl<'a, S: OreCipher, const N: usize> Clone for CipherTextRef<'a, S, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, S: OreCipher, const N: usize> Copy for CipherTextRef<'a, S, N> {}
+
+/* Check that every block in a slice of concatenated blocks parses */
+fn validate_blocks<B: CipherTextBlock>(data: &[u8]) -> Result<(), ParseError> {
+    data.chunks(B::BLOCK_SIZE)
+        .try_for_each(|block| B::from_bytes(block).map(|_| ()))
+}
+
+impl<'a, S: OreCipher, const N: usize> LeftRef<'a, S, N> {
+    /// Wrap a serialized [`Left`] ciphertext, checking its length and blocks.
+    pub fn new(data: &'a [u8]) -> Result<Self, ParseError> {
+        ParseError::check_length(Left::<S, N>::size(), data.len())?;
+        validate_blocks::<S::LeftBlockType>(&data[N..])?;
+
+        Ok(Self {
+            data,
+            scheme: PhantomData,
+        })
+    }
+
+    /// The transformed input (one byte per block)
+    pub fn xt(&self) -> &'a [u8] {
+        &self.data[..N]
+    }
+
+    /// The serialized left blocks
+    pub fn f(&self) -> ChunksExact<'a, u8> {
+        self.data[N..].chunks_exact(S::LeftBlockType::BLOCK_SIZE)
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+impl<'a, S: OreCipher, const N: usize> CipherTextRef<'a, S, N> {
+    /// Wrap a serialized [`CipherText`], checking its length and blocks.
+    pub fn new(data: &'a [u8]) -> Result<Self, ParseError> {
+        ParseError::check_length(CipherText::<S, N>::size(), data.len())?;
+        let (left, right) = data.split_at(Left::<S, N>::size());
+        LeftRef::<S, N>::new(left)?;
+        validate_blocks::<S::RightBlockType>(&right[NONCE_SIZE..])?;
+
+        Ok(Self {
+            data,
+            scheme: PhantomData,
+        })
+    }
+
+    pub fn left(&self) -> LeftRef<'a, S, N> {
+        LeftRef {
+            data: &self.data[..Left::<S, N>::size()],
+            scheme: PhantomData,
+        }
+    }
+
+    pub fn nonce(&self) -> &'a [u8] {
+        let start = Left::<S, N>::size();
+        &self.data[start..(start + NONCE_SIZE)]
+    }
+
+    /// The serialized right blocks
+    pub fn data(&self) -> ChunksExact<'a, u8> {
+        self.data[(Left::<S, N>::size() + NONCE_SIZE)..].chunks_exact(S::RightBlockType::BLOCK_SIZE)
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/*
+ * Both sides of a comparison were checked to have the sizes expected for N blocks when they were
+ * created so the raw comparisons can't fail.
+ */
+impl<'a, S: OreCipher, const N: usize> Ord for CipherTextRef<'a, S, N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        S::compare_raw_slices(self.data, other.data).expect("ciphertext views have the same size")
+    }
+}
+
+impl<'a, S: OreCipher, const N: usize> PartialOrd for CipherTextRef<'a, S, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, S: OreCipher, const N: usize> PartialEq for CipherTextRef<'a, S, N> {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(self.cmp(other), Ordering::Equal)
+    }
+}
+
+impl<'a, S: OreCipher, const N: usize> Eq for CipherTextRef<'a, S, N> {}
+
+impl<'a, 'b, S: OreCipher, const N: usize> PartialOrd<CipherTextRef<'b, S, N>>
+    for LeftRef<'a, S, N>
+{
+    fn partial_cmp(&self, other: &CipherTextRef<'b, S, N>) -> Option<Ordering> {
+        S::compare_left_raw_slices(self.data, other.data)
+    }
+}
+
+impl<'a, 'b, S: OreCipher, const N: usize> PartialEq<CipherTextRef<'b, S, N>>
+    for LeftRef<'a, S, N>
+{
+    fn eq(&self, other: &CipherTextRef<'b, S, N>) -> bool {
+        matches!(self.partial_cmp(other), Some(Ordering::Equal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::{bit2, bit3};
+    use crate::test_utils::init_ore;
+    use crate::OreEncrypt;
+
+    type Ore = bit2::OreAes128ChaCha20;
+
+    quickcheck! {
+        fn compare_views(x: u64, y: u64) -> bool {
+            let ore: Ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+            let (a_bytes, b_bytes) = (a.to_bytes(), b.to_bytes());
+            let left_bytes = a.left.to_bytes();
+            let a_ref = CipherTextRef::<Ore, 8>::new(&a_bytes).unwrap();
+            let b_ref = CipherTextRef::<Ore, 8>::new(&b_bytes).unwrap();
+            let left_ref = LeftRef::<Ore, 8>::new(&left_bytes).unwrap();
+
+            a_ref.cmp(&b_ref) == x.cmp(&y)
+                && a_ref.cmp(&b_ref) == a.cmp(&b)
+                && left_ref.partial_cmp(&b_ref) == Some(x.cmp(&y))
+        }
+
+        fn compare_views_bit3(x: u32, y: u32) -> bool {
+            let ore: bit3::OreAes128ChaCha20 = init_ore();
+            let a = x.encrypt(&ore).unwrap().to_bytes();
+            let b = y.encrypt(&ore).unwrap().to_bytes();
+
+            CipherTextRef::<bit3::OreAes128ChaCha20, 4>::new(&a).unwrap()
+                .cmp(&CipherTextRef::new(&b).unwrap()) == x.cmp(&y)
+        }
+
+        fn sort_page(values: Vec<u32>) -> bool {
+            let ore: Ore = init_ore();
+            let page: Vec<u8> = values
+                .iter()
+                .flat_map(|x| x.encrypt(&ore).unwrap().to_bytes())
+                .collect();
+
+            let mut views: Vec<_> = page
+                .chunks(CipherText::<Ore, 4>::size())
+                .map(|ct| CipherTextRef::<Ore, 4>::new(ct).unwrap())
+                .collect();
+            views.sort();
+
+            let mut sorted = values.clone();
+            sorted.sort();
+            views
+                .iter()
+                .map(|view| u32::decrypt(&CipherText::from_slice(view.as_bytes()).unwrap(), &ore).unwrap())
+                .eq(sorted)
+        }
+
+        fn fields_match_owned(x: u64) -> bool {
+            let ore: Ore = init_ore();
+            let ct = x.encrypt(&ore).unwrap();
+            let bytes = ct.to_bytes();
+            let view = CipherTextRef::<Ore, 8>::new(&bytes).unwrap();
+
+            view.left().xt() == ct.left.xt
+                && view.left().f().eq(ct.left.f.iter().map(|block| &block[..]))
+                && view.nonce() == ct.right.nonce
+                && view.data().map(<[u8]>::to_vec).eq(ct.right.data.iter().map(|block| block.to_bytes()))
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_data() {
+        let ore: bit3::OreAes128ChaCha20 = init_ore();
+        let ct = 42u32.encrypt(&ore).unwrap();
+        let bytes = ct.to_bytes();
+
+        assert!(CipherTextRef::<bit3::OreAes128ChaCha20, 4>::new(&bytes[1..]).is_err());
+        assert!(CipherTextRef::<bit3::OreAes128ChaCha20, 2>::new(&bytes).is_err());
+        assert!(LeftRef::<bit3::OreAes128ChaCha20, 4>::new(&bytes).is_err());
+
+        /* Right blocks for bit3 can't contain bytes above 242 */
+        let mut invalid = bytes.clone();
+        *invalid.last_mut().unwrap() = 255;
+        assert_eq!(
+            CipherTextRef::<bit3::OreAes128ChaCha20, 4>::new(&invalid)
+                .unwrap_err()
+                .reason(),
+            ParseErrorReason::InvalidBlock
+        );
+    }
+}
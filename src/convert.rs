@@ -14,30 +14,100 @@
   The mapping does not preserve any notion of the previous value after the
   conversion - only ordering is preserved.
 
-  Caveat: NaN and -ve & +ve infinity will also be mapped and ordering is not
-  well-defined with those values. Those values should be discarded before
-  converting arrays of those values.
+  Every float is mapped by its bit pattern so the ordering of the integers
+  matches the IEEE 754 total order: -0.0 sorts before +0.0 and NaNs sort
+  below -infinity or above +infinity depending on their sign bit. Use a
+  FloatPolicy to reject or canonicalise those values before encrypting them.
 
   This post was used as a reference for building this implementation:
   https://lemire.me/blog/2020/12/14/converting-floating-point-numbers-to-integers-while-preserving-order
 */
 
-use core::mem;
+use crate::OreError;
+
+/// How float values without an obvious ordering are treated before encryption.
+///
+/// The [`OreEncrypt`](crate::OreEncrypt) implementations for `f32` and `f64` encrypt every value
+/// by its bit pattern (see [`FloatPolicy::Raw`]). Encrypt values with
+/// [`OreEncryptFloat`](crate::OreEncryptFloat) to apply a stricter policy and get a well-defined
+/// total order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FloatPolicy {
+    /// Leave values untouched. Ciphertexts follow the IEEE 754 total order so `-0.0 < 0.0` and
+    /// NaNs sort at either end depending on their sign bit.
+    #[default]
+    Raw,
+    /// Fail with [`OreError::InvalidFloat`] for NaN and ±infinity and fold `-0.0` into `0.0`.
+    Reject,
+    /// Fold `-0.0` into `0.0` and map every NaN to a single NaN that sorts above `+infinity`.
+    /// This matches the `ORDER BY` semantics of SQL databases such as PostgreSQL.
+    Canonicalize,
+}
+
+macro_rules! impl_float_policy {
+    ($apply:ident, $float:ty, $canonical_nan:literal) => {
+        /// Apply the policy to a
+        #[doc = concat!("`", stringify!($float), "`")]
+        /// value, returning the value to encrypt.
+        pub fn $apply(self, input: $float) -> Result<$float, OreError> {
+            match self {
+                FloatPolicy::Raw => Ok(input),
+                FloatPolicy::Reject if !input.is_finite() => Err(OreError::InvalidFloat),
+                FloatPolicy::Canonicalize if input.is_nan() => {
+                    Ok(<$float>::from_bits($canonical_nan))
+                }
+                // Adding 0.0 turns -0.0 into 0.0 and leaves everything else alone
+                _ => Ok(input + 0.0),
+            }
+        }
+    };
+}
+
+impl FloatPolicy {
+    impl_float_policy!(apply_f64, f64, 0x7ff8_0000_0000_0000);
+    impl_float_policy!(apply_f32, f32, 0x7fc0_0000);
+}
 
 pub(crate) trait ToOrderedInteger<T> {
     fn map_to(&self) -> T;
 }
 
-#[allow(dead_code)]
-trait FromOrderedInteger<T> {
+pub(crate) trait FromOrderedInteger<T> {
     fn map_from(input: T) -> Self;
 }
 
+/*
+  Signed integers are mapped by flipping the sign bit. This moves negative
+  numbers below positive ones while two's complement already preserves the
+  ordering within each half.
+*/
+macro_rules! impl_ordered_integer_for_signed {
+    ($signed:ty, $unsigned:ty) => {
+        impl ToOrderedInteger<$unsigned> for $signed {
+            fn map_to(&self) -> $unsigned {
+                (*self as $unsigned) ^ (1 << (<$unsigned>::BITS - 1))
+            }
+        }
+
+        impl FromOrderedInteger<$unsigned> for $signed {
+            fn map_from(input: $unsigned) -> $signed {
+                (input ^ (1 << (<$unsigned>::BITS - 1))) as $signed
+            }
+        }
+    };
+}
+
+impl_ordered_integer_for_signed!(i8, u8);
+impl_ordered_integer_for_signed!(i16, u16);
+impl_ordered_integer_for_signed!(i32, u32);
+impl_ordered_integer_for_signed!(i64, u64);
+impl_ordered_integer_for_signed!(i128, u128);
+
 impl ToOrderedInteger<u64> for f64 {
     fn map_to(&self) -> u64 {
         let num: u64 = self.to_bits();
-        let signed: i64 = -(unsafe { mem::transmute(num >> 63) });
-        let mut mask: u64 = unsafe { mem::transmute(signed) };
+        let signed: i64 = -((num >> 63) as i64);
+        let mut mask: u64 = signed as u64;
         mask |= 0x8000000000000000;
         num ^ mask
     }
@@ -51,6 +121,24 @@ impl FromOrderedInteger<u64> for f64 {
     }
 }
 
+impl ToOrderedInteger<u32> for f32 {
+    fn map_to(&self) -> u32 {
+        let num: u32 = self.to_bits();
+        let signed: i32 = -((num >> 31) as i32);
+        let mut mask: u32 = signed as u32;
+        mask |= 0x80000000;
+        num ^ mask
+    }
+}
+
+impl FromOrderedInteger<u32> for f32 {
+    fn map_from(input: u32) -> f32 {
+        let i = (((input >> 31) as i32) - 1) as u32;
+        let mask: u32 = i | 0x80000000;
+        f32::from_bits(input ^ mask)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,5 +152,116 @@ mod tests {
                 TestResult::discard()
             }
         }
+
+        fn roundtrip_f32(x: f32) -> TestResult {
+            if !x.is_nan() && x.is_finite() {
+                TestResult::from_bool(x == f32::map_from(x.map_to()))
+            } else {
+                TestResult::discard()
+            }
+        }
+
+        /*
+         * The mapping follows the IEEE 754 total order (so -0.0 < 0.0)
+         */
+        fn order_f32(x: f32, y: f32) -> bool {
+            let a: u32 = x.map_to();
+            let b: u32 = y.map_to();
+
+            x.total_cmp(&y) == a.cmp(&b)
+        }
+
+        fn roundtrip_i64(x: i64) -> bool {
+            x == i64::map_from(x.map_to())
+        }
+
+        fn roundtrip_i8(x: i8) -> bool {
+            x == i8::map_from(x.map_to())
+        }
+
+        fn order_i64(x: i64, y: i64) -> bool {
+            let a: u64 = x.map_to();
+            let b: u64 = y.map_to();
+
+            x.cmp(&y) == a.cmp(&b)
+        }
+
+        fn order_i16(x: i16, y: i16) -> bool {
+            let a: u16 = x.map_to();
+            let b: u16 = y.map_to();
+
+            x.cmp(&y) == a.cmp(&b)
+        }
+    }
+
+    quickcheck! {
+        fn canonicalize_f64_keeps_order(x: f64, y: f64) -> bool {
+            let a: u64 = FloatPolicy::Canonicalize.apply_f64(x).unwrap().map_to();
+            let b: u64 = FloatPolicy::Canonicalize.apply_f64(y).unwrap().map_to();
+
+            match x.partial_cmp(&y) {
+                Some(ordering) => a.cmp(&b) == ordering,
+                None => (x.is_nan() && a >= b) || (y.is_nan() && a <= b)
+            }
+        }
+
+        fn reject_f32(x: f32) -> bool {
+            FloatPolicy::Reject.apply_f32(x).is_ok() == x.is_finite()
+        }
+    }
+
+    #[test]
+    fn canonical_zero() {
+        for policy in [FloatPolicy::Reject, FloatPolicy::Canonicalize] {
+            let zero = policy.apply_f64(-0.0).unwrap();
+            assert_eq!(zero.to_bits(), 0.0f64.to_bits());
+
+            let zero = policy.apply_f32(-0.0).unwrap();
+            assert_eq!(zero.to_bits(), 0.0f32.to_bits());
+        }
+
+        assert_eq!(
+            FloatPolicy::Raw.apply_f64(-0.0).unwrap().to_bits(),
+            (-0.0f64).to_bits()
+        );
+    }
+
+    #[test]
+    fn canonical_nan() {
+        let negative_nan = f64::from_bits(0xfff8_0000_0000_0001);
+        let a: u64 = FloatPolicy::Canonicalize
+            .apply_f64(negative_nan)
+            .unwrap()
+            .map_to();
+        let b: u64 = FloatPolicy::Canonicalize
+            .apply_f64(f64::NAN)
+            .unwrap()
+            .map_to();
+        let inf: u64 = f64::INFINITY.map_to();
+
+        assert_eq!(a, b);
+        assert!(a > inf);
+
+        let nan = FloatPolicy::Canonicalize.apply_f32(-f32::NAN).unwrap();
+        assert_eq!(nan.to_bits(), 0x7fc0_0000);
+    }
+
+    #[test]
+    fn reject_non_finite() {
+        assert!(matches!(
+            FloatPolicy::Reject.apply_f64(f64::NAN),
+            Err(OreError::InvalidFloat)
+        ));
+        assert!(FloatPolicy::Reject.apply_f64(f64::NEG_INFINITY).is_err());
+        assert!(FloatPolicy::Reject.apply_f32(f32::INFINITY).is_err());
+        assert_eq!(FloatPolicy::Reject.apply_f64(1.5).unwrap(), 1.5);
+    }
+
+    #[test]
+    fn signed_extremes() {
+        assert_eq!(i32::MIN.map_to(), 0u32);
+        assert_eq!((-1i32).map_to(), 0x7fff_ffffu32);
+        assert_eq!(0i32.map_to(), 0x8000_0000u32);
+        assert_eq!(i32::MAX.map_to(), u32::MAX);
     }
 }
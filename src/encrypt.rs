@@ -1,7 +1,8 @@
 use crate::ciphertext::*;
-use crate::convert::ToOrderedInteger;
+use crate::convert::{FloatPolicy, FromOrderedInteger, ToOrderedInteger};
 use crate::PlainText;
 use crate::{OreCipher, OreError};
+use std::cmp::Ordering;
 
 pub trait OreEncrypt<T: OreCipher> {
     type LeftOutput: OreOutput;
@@ -9,71 +10,520 @@ pub trait OreEncrypt<T: OreCipher> {
 
     fn encrypt_left(&self, cipher: &T) -> Result<Self::LeftOutput, OreError>;
     fn encrypt(&self, input: &T) -> Result<Self::FullOutput, OreError>;
-}
-
-impl<T: OreCipher> OreEncrypt<T> for u64 {
-    /* Note that Rust currently doesn't allow
-     * generic associated types so this ia a bit verbose! */
-    type LeftOutput = Left<T, 8>;
-    type FullOutput = CipherText<T, 8>;
 
-    fn encrypt_left(&self, cipher: &T) -> Result<Self::LeftOutput, OreError>
+    /*
+     * Decryption is optional for implementors so the defaults fail with OreError::DecryptFailed
+     */
+    fn decrypt_left(_input: &Self::LeftOutput, _cipher: &T) -> Result<Self, OreError>
     where
-        T::LeftBlockType: CipherTextBlock,
+        Self: Sized,
     {
-        let bytes = self.to_be_bytes();
-        cipher.encrypt_left(&bytes)
+        Err(OreError::DecryptFailed)
     }
 
-    fn encrypt(&self, cipher: &T) -> Result<Self::FullOutput, OreError>
+    fn decrypt(_input: &Self::FullOutput, _cipher: &T) -> Result<Self, OreError>
     where
-        T::LeftBlockType: CipherTextBlock,
-        T::RightBlockType: CipherTextBlock,
+        Self: Sized,
     {
-        let bytes = self.to_be_bytes();
-        cipher.encrypt(&bytes)
+        Err(OreError::DecryptFailed)
     }
 }
 
-impl<T: OreCipher> OreEncrypt<T> for u32 {
-    type LeftOutput = Left<T, 4>;
-    type FullOutput = CipherText<T, 4>;
+/// A type with an order-preserving encoding into a fixed size [`PlainText`].
+///
+/// For any values `a` and `b`, `a.encode().cmp(&b.encode())` must equal the ordering of `a` and
+/// `b`, and `decode` must reverse `encode`. Every type implementing `OrePlaintext` with
+/// `Encoded = PlainText<N>` implements [`OreEncrypt`], producing ciphertexts with `N` blocks.
+///
+/// All of the types supported by `OreEncrypt` are implemented this way so their encodings can be
+/// used directly, for example to decode a plaintext returned by [`OreCipher::decrypt`]:
+///
+/// ```rust
+/// # use ore_rs::{
+/// #     OreCipher,  // Main ORE Cipher trait
+/// #     OreEncrypt, // Traits for encrypting primitive types (e.g. u64)
+/// #     scheme::bit2::OreAes128ChaCha20 // Specific scheme we want to use
+/// # };
+/// # use hex_literal::hex;
+/// use ore_rs::{OreError, OrePlaintext, PlainText};
+/// # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Version {
+///     major: u8,
+///     minor: u8,
+///     patch: u8,
+/// }
+///
+/// impl OrePlaintext for Version {
+///     type Encoded = PlainText<3>;
+///
+///     fn encode(&self) -> PlainText<3> {
+///         [self.major, self.minor, self.patch]
+///     }
+///
+///     fn decode(input: &PlainText<3>) -> Result<Self, OreError> {
+///         Ok(Version { major: input[0], minor: input[1], patch: input[2] })
+///     }
+/// }
+///
+/// let a = Version { major: 1, minor: 10, patch: 0 }.encrypt(&ore).unwrap();
+/// let b = Version { major: 2, minor: 0, patch: 1 }.encrypt(&ore).unwrap();
+/// assert!(a < b);
+///
+/// let plaintext = ore.decrypt(&b).unwrap();
+/// assert_eq!(Version::decode(&plaintext).unwrap(), Version { major: 2, minor: 0, patch: 1 });
+/// assert_eq!(i32::decode(&(-5i32).encode()).unwrap(), -5);
+/// ```
+pub trait OrePlaintext: Sized {
+    /// The plaintext the value is encoded as. This is always a [`PlainText<N>`] but can't be
+    /// written as a const generic parameter of the trait on stable Rust.
+    type Encoded;
+
+    fn encode(&self) -> Self::Encoded;
+
+    /// Decode a value from its encoding.
+    ///
+    /// Fails with [`OreError::DecryptFailed`] for plaintexts that aren't the encoding of any
+    /// value (e.g. a plaintext that was encrypted from a different type).
+    fn decode(input: &Self::Encoded) -> Result<Self, OreError>;
+}
+
+/*
+ * N is bound through the encoding of P as it can't be a parameter of OrePlaintext itself
+ */
+impl<T, P, const N: usize> OreEncrypt<T> for P
+where
+    T: OreCipher,
+    P: OrePlaintext<Encoded = PlainText<N>>,
+{
+    type LeftOutput = Left<T, N>;
+    type FullOutput = CipherText<T, N>;
 
     fn encrypt_left(&self, cipher: &T) -> Result<Self::LeftOutput, OreError> {
-        let bytes = self.to_be_bytes();
-        cipher.encrypt_left(&bytes)
+        cipher.encrypt_left(&self.encode())
     }
 
     fn encrypt(&self, cipher: &T) -> Result<Self::FullOutput, OreError> {
-        let bytes = self.to_be_bytes();
-        cipher.encrypt(&bytes)
+        cipher.encrypt(&self.encode())
+    }
+
+    fn decrypt_left(input: &Self::LeftOutput, cipher: &T) -> Result<Self, OreError> {
+        cipher
+            .decrypt_left(input)
+            .and_then(|plaintext| P::decode(&plaintext))
+    }
+
+    fn decrypt(input: &Self::FullOutput, cipher: &T) -> Result<Self, OreError> {
+        cipher
+            .decrypt(input)
+            .and_then(|plaintext| P::decode(&plaintext))
     }
 }
 
-impl<T: OreCipher> OreEncrypt<T> for f64 {
-    type LeftOutput = Left<T, 8>;
-    type FullOutput = CipherText<T, 8>;
+/// Encryption of floats under an explicit [`FloatPolicy`].
+///
+/// [`OreEncrypt`] encrypts floats under [`FloatPolicy::Raw`]. These methods apply the given policy
+/// first so NaN, ±infinity and `-0.0` are rejected or canonicalised as part of encryption.
+pub trait OreEncryptFloat<T: OreCipher>: OreEncrypt<T> {
+    fn encrypt_left_with_policy(
+        &self,
+        cipher: &T,
+        policy: FloatPolicy,
+    ) -> Result<Self::LeftOutput, OreError>;
 
-    fn encrypt_left(&self, cipher: &T) -> Result<Self::LeftOutput, OreError> {
-        let plaintext: u64 = self.map_to();
-        plaintext.encrypt_left(cipher)
+    fn encrypt_with_policy(
+        &self,
+        cipher: &T,
+        policy: FloatPolicy,
+    ) -> Result<Self::FullOutput, OreError>;
+}
+
+macro_rules! impl_ore_encrypt_float {
+    ($($float:ty => $apply:ident),*) => {
+        $(
+            impl<T: OreCipher> OreEncryptFloat<T> for $float {
+                fn encrypt_left_with_policy(
+                    &self,
+                    cipher: &T,
+                    policy: FloatPolicy,
+                ) -> Result<Self::LeftOutput, OreError> {
+                    policy.$apply(*self)?.encrypt_left(cipher)
+                }
+
+                fn encrypt_with_policy(
+                    &self,
+                    cipher: &T,
+                    policy: FloatPolicy,
+                ) -> Result<Self::FullOutput, OreError> {
+                    policy.$apply(*self)?.encrypt(cipher)
+                }
+            }
+        )*
+    };
+}
+
+impl_ore_encrypt_float!(f32 => apply_f32, f64 => apply_f64);
+
+/*
+ * The encoding of a plaintext type as a slice. Composite keys are encoded as the concatenation of
+ * the encodings of their components which needs their sizes as constants.
+ */
+pub trait OrderedBytes: Sized {
+    const SIZE: usize;
+
+    /* Write the encoding into out which is exactly SIZE bytes long */
+    fn write_ordered(&self, out: &mut [u8]);
+
+    /*
+     * Read a value back from exactly SIZE bytes.
+     * Fails for bytes that aren't the encoding of any value.
+     */
+    fn read_ordered(data: &[u8]) -> Result<Self, OreError>;
+}
+
+impl<P, const N: usize> OrderedBytes for P
+where
+    P: OrePlaintext<Encoded = PlainText<N>>,
+{
+    const SIZE: usize = N;
+
+    fn write_ordered(&self, out: &mut [u8]) {
+        out.copy_from_slice(&self.encode());
     }
 
-    fn encrypt(&self, cipher: &T) -> Result<Self::FullOutput, OreError> {
-        let plaintext: u64 = self.map_to();
-        plaintext.encrypt(cipher)
+    fn read_ordered(data: &[u8]) -> Result<Self, OreError> {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(data);
+        P::decode(&bytes)
     }
 }
 
-impl<T: OreCipher, const N: usize> OreEncrypt<T> for PlainText<N> {
-    type LeftOutput = Left<T, N>;
-    type FullOutput = CipherText<T, N>;
+/* Build an encoding by writing it into a zeroed plaintext. N is inferred from the caller. */
+pub fn encode_with<const N: usize>(write: impl FnOnce(&mut [u8])) -> PlainText<N> {
+    let mut bytes = [0; N];
+    write(&mut bytes);
+    bytes
+}
 
-    fn encrypt_left(&self, cipher: &T) -> Result<Self::LeftOutput, OreError> {
-        cipher.encrypt_left(self)
+/*
+ * Descending components are encoded as the bitwise complement of their encoding
+ * which reverses the ordering
+ */
+pub fn write_desc<P: OrderedBytes>(value: &P, out: &mut [u8]) {
+    value.write_ordered(out);
+    out.iter_mut().for_each(|b| *b = !*b);
+}
+
+pub fn read_desc<P: OrderedBytes>(data: &[u8]) -> Result<P, OreError> {
+    let bytes: Vec<u8> = data.iter().map(|b| !b).collect();
+    P::read_ordered(&bytes)
+}
+
+/* Unsigned integers are encoded in big endian byte order */
+macro_rules! impl_ore_plaintext_for_unsigned {
+    ($($unsigned:ty),*) => {
+        $(
+            impl OrePlaintext for $unsigned {
+                type Encoded = PlainText<{ std::mem::size_of::<$unsigned>() }>;
+
+                fn encode(&self) -> Self::Encoded {
+                    self.to_be_bytes()
+                }
+
+                fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+                    Ok(<$unsigned>::from_be_bytes(*input))
+                }
+            }
+        )*
+    };
+}
+
+/*
+ * Signed integers and floats are encoded as their order-preserving unsigned mapping
+ * (see convert.rs)
+ */
+macro_rules! impl_ore_plaintext_for_mapped {
+    ($($mapped:ty => $unsigned:ty),*) => {
+        $(
+            impl OrePlaintext for $mapped {
+                type Encoded = <$unsigned as OrePlaintext>::Encoded;
+
+                fn encode(&self) -> Self::Encoded {
+                    let plaintext: $unsigned = self.map_to();
+                    plaintext.encode()
+                }
+
+                fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+                    <$unsigned>::decode(input).map(<$mapped>::map_from)
+                }
+            }
+        )*
+    };
+}
+
+impl_ore_plaintext_for_unsigned!(u8, u16, u32, u64, u128);
+impl_ore_plaintext_for_mapped!(
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, f32 => u32, f64 => u64
+);
+
+impl<const N: usize> OrePlaintext for PlainText<N> {
+    type Encoded = PlainText<N>;
+
+    fn encode(&self) -> Self::Encoded {
+        *self
     }
 
-    fn encrypt(&self, cipher: &T) -> Result<Self::FullOutput, OreError> {
-        cipher.encrypt(self)
+    fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+        Ok(*input)
     }
 }
+
+/*
+ * Pairs are encrypted as the concatenation of their components' encodings so ciphertexts order
+ * lexicographically: by the first component, then by the second.
+ *
+ * Stable Rust can't add the const sizes of generic components so the impls are generated for
+ * every pair of the supported primitive types.
+ */
+macro_rules! impl_ore_plaintext_for_pair {
+    ($a:ty, $b:ty) => {
+        impl OrePlaintext for ($a, $b) {
+            type Encoded = PlainText<{ <$a as OrderedBytes>::SIZE + <$b as OrderedBytes>::SIZE }>;
+
+            fn encode(&self) -> Self::Encoded {
+                encode_with(|out| {
+                    let (a, b) = out.split_at_mut(<$a as OrderedBytes>::SIZE);
+                    self.0.write_ordered(a);
+                    self.1.write_ordered(b);
+                })
+            }
+
+            fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+                let (a, b) = input.split_at(<$a as OrderedBytes>::SIZE);
+                Ok((<$a>::read_ordered(a)?, <$b>::read_ordered(b)?))
+            }
+        }
+    };
+}
+
+macro_rules! impl_ore_plaintext_for_pairs {
+    ($($a:ty),*) => {
+        impl_ore_plaintext_for_pairs!(@first [$($a),*] [$($a),*]);
+    };
+    (@first [$($a:ty),*] $second:tt) => {
+        $(impl_ore_plaintext_for_pairs!(@second $a $second);)*
+    };
+    (@second $a:ty [$($b:ty),*]) => {
+        $(impl_ore_plaintext_for_pair!($a, $b);)*
+    };
+}
+
+impl_ore_plaintext_for_pairs!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+/// A plaintext wrapper which encrypts to ciphertexts in the reverse order of the wrapped value.
+///
+/// The wrapped value is encoded as the bitwise complement of its usual encoding, so unlike
+/// wrapping ciphertexts in [`std::cmp::Reverse`], serialized ciphertexts compared with
+/// [`OreCipher::compare_raw_slices`] are in descending order, too.
+///
+/// ```rust
+/// # use ore_rs::{
+/// #     Descending,
+/// #     OreCipher,  // Main ORE Cipher trait
+/// #     OreEncrypt, // Traits for encrypting primitive types (e.g. u64)
+/// #     scheme::bit2::OreAes128ChaCha20 // Specific scheme we want to use
+/// # };
+/// # use hex_literal::hex;
+/// # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+/// let a = Descending(10u64).encrypt(&ore).unwrap();
+/// let b = Descending(20u64).encrypt(&ore).unwrap();
+///
+/// assert!(a > b);
+/// assert_eq!(Descending::<u64>::decrypt(&a, &ore).unwrap(), Descending(10));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Descending<P>(pub P);
+
+impl<P: PartialOrd> PartialOrd for Descending<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        other.0.partial_cmp(&self.0)
+    }
+}
+
+impl<P: Ord> Ord for Descending<P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.cmp(&self.0)
+    }
+}
+
+impl<P, const N: usize> OrePlaintext for Descending<P>
+where
+    P: OrePlaintext<Encoded = PlainText<N>>,
+{
+    type Encoded = PlainText<N>;
+
+    fn encode(&self) -> Self::Encoded {
+        encode_with(|out| write_desc(&self.0, out))
+    }
+
+    fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+        read_desc(input).map(Descending)
+    }
+}
+
+/*
+ * Nullable values are encoded as a marker byte followed by the encoding of the value (or zeros
+ * for nulls). The marker decides whether nulls order before or after every other value.
+ */
+const NULL_MARKER_LOW: u8 = 0;
+const NULL_MARKER_HIGH: u8 = 1;
+
+fn write_nullable<P: OrderedBytes>(value: &Option<P>, null_marker: u8, out: &mut [u8]) {
+    let (marker, rest) = out.split_at_mut(1);
+    match value {
+        None => {
+            marker[0] = null_marker;
+            rest.iter_mut().for_each(|b| *b = 0);
+        }
+        Some(value) => {
+            marker[0] = null_marker ^ 1;
+            value.write_ordered(rest);
+        }
+    }
+}
+
+fn read_nullable<P: OrderedBytes>(data: &[u8], null_marker: u8) -> Result<Option<P>, OreError> {
+    match data[0] {
+        /* Nulls are written with zeroed padding so anything else isn't the encoding of a value */
+        marker if marker == null_marker && data[1..].iter().all(|b| *b == 0) => Ok(None),
+        marker if marker == null_marker ^ 1 => P::read_ordered(&data[1..]).map(Some),
+        _ => Err(OreError::DecryptFailed),
+    }
+}
+
+/// A nullable plaintext whose nulls (`None`) order after every other value (`NULLS LAST`).
+///
+/// `Option<T>` encrypts with nulls first. Both work for any [`OrePlaintext`] whose encoding is at most
+/// 255 bytes long.
+///
+/// ```rust
+/// # use ore_rs::{
+/// #     NullsLast,
+/// #     OreCipher,  // Main ORE Cipher trait
+/// #     OreEncrypt, // Traits for encrypting primitive types (e.g. u64)
+/// #     scheme::bit2::OreAes128ChaCha20 // Specific scheme we want to use
+/// # };
+/// # use hex_literal::hex;
+/// # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+/// let null = None::<u64>.encrypt(&ore).unwrap();
+/// let value = Some(10u64).encrypt(&ore).unwrap();
+/// assert!(null < value);
+///
+/// let null = NullsLast(None::<u64>).encrypt(&ore).unwrap();
+/// let value = NullsLast(Some(10u64)).encrypt(&ore).unwrap();
+/// assert!(null > value);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NullsLast<P>(pub Option<P>);
+
+impl<P: PartialOrd> PartialOrd for NullsLast<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (&self.0, &other.0) {
+            (None, None) => Some(Ordering::Equal),
+            (None, Some(_)) => Some(Ordering::Greater),
+            (Some(_), None) => Some(Ordering::Less),
+            (Some(a), Some(b)) => a.partial_cmp(b),
+        }
+    }
+}
+
+impl<P: Ord> Ord for NullsLast<P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (&self.0, &other.0) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => a.cmp(b),
+        }
+    }
+}
+
+/*
+ * The encoding of a nullable value is one byte longer than the encoding of the value. Stable Rust
+ * can't compute N + 1 for a generic N so this maps each supported size to the next one.
+ */
+pub trait NullMarked {
+    type Marked;
+}
+
+macro_rules! impl_null_marked {
+    ([$($hi:literal)*] $lo:tt) => {
+        $(impl_null_marked!(@row $hi $lo);)*
+    };
+    (@row $hi:literal [$($lo:literal)*]) => {
+        $(
+            impl NullMarked for PlainText<{ $hi * 16 + $lo }> {
+                type Marked = PlainText<{ $hi * 16 + $lo + 1 }>;
+            }
+        )*
+    };
+}
+
+/* Values with encodings of up to 255 bytes can be nullable */
+impl_null_marked!(
+    [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15]
+    [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15]
+);
+
+/// Nulls (`None`) order before every other value (`NULLS FIRST`), matching the [`Ord`] of `Option`.
+impl<P, const N: usize, const M: usize> OrePlaintext for Option<P>
+where
+    P: OrePlaintext<Encoded = PlainText<N>>,
+    PlainText<N>: NullMarked<Marked = PlainText<M>>,
+{
+    type Encoded = PlainText<M>;
+
+    fn encode(&self) -> Self::Encoded {
+        encode_with(|out| write_nullable(self, NULL_MARKER_LOW, out))
+    }
+
+    fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+        read_nullable(input, NULL_MARKER_LOW)
+    }
+}
+
+impl<P, const N: usize, const M: usize> OrePlaintext for NullsLast<P>
+where
+    P: OrePlaintext<Encoded = PlainText<N>>,
+    PlainText<N>: NullMarked<Marked = PlainText<M>>,
+{
+    type Encoded = PlainText<M>;
+
+    fn encode(&self) -> Self::Encoded {
+        encode_with(|out| write_nullable(&self.0, NULL_MARKER_HIGH, out))
+    }
+
+    fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+        read_nullable(input, NULL_MARKER_HIGH).map(NullsLast)
+    }
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod datetime;
+
+pub mod collation;
+mod fixed_point;
+#[cfg(feature = "uuid")]
+mod identifier;
+mod ip;
+pub use self::fixed_point::FixedPoint;
+#[cfg(feature = "rust_decimal")]
+pub use self::fixed_point::{DecimalEncoding, Scaled};
+pub use self::ip::Cidr;
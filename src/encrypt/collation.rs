@@ -0,0 +1,174 @@
+//! Order-preserving sort keys for strings.
+//!
+//! A [`Collation`] maps a string to a sort key: a sequence of bytes which is compared
+//! lexicographically, byte by byte. Encrypting the sort key instead of the string lets encrypted
+//! comparisons use a different ordering than the UTF-8 bytes of the string, such as ignoring case.
+//!
+//! The provided collations ([`Binary`] and [`Lowercase`]) order by code point. They are not
+//! locale-aware collations: accented letters sort after every ASCII letter (`"Zoë" < "Émile"`) and
+//! no language-specific rules are applied.
+//!
+//! Sort keys can either be encrypted as variable length plaintexts (see
+//! [`OreAes128::encrypt_var`](crate::scheme::bit2::OreAes128::encrypt_var)) or encoded into a
+//! fixed size [`PlainText`] which works with [`OreEncrypt`](crate::OreEncrypt).
+//!
+//! ```rust
+//! # use ore_rs::{
+//! #     OreCipher,  // Main ORE Cipher trait
+//! #     OreEncrypt, // Traits for encrypting primitive types (e.g. u64)
+//! #     scheme::bit2::OreAes128ChaCha20 // Specific scheme we want to use
+//! # };
+//! # use hex_literal::hex;
+//! use ore_rs::collation::{Collation, Lowercase};
+//! # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+//!
+//! let a = ore.encrypt_var(&Lowercase.sort_key("apple"), None).unwrap();
+//! let b = ore.encrypt_var(&Lowercase.sort_key("Banana"), None).unwrap();
+//! assert!(a < b);
+//!
+//! let c = Lowercase.encode::<16>("APPLE").unwrap().encrypt(&ore).unwrap();
+//! let d = Lowercase.encode::<16>("apple").unwrap().encrypt(&ore).unwrap();
+//! assert!(c == d);
+//! ```
+//!
+//! Locale-aware orderings (such as the Unicode Collation Algorithm) can be used by implementing
+//! [`Collation`] on top of a library that produces sort keys (e.g. ICU's `ucol_getSortKey`).
+
+use super::*;
+
+/// A mapping from strings to sort keys which preserves the order of a collation.
+pub trait Collation {
+    /// The sort key for `input`.
+    ///
+    /// For any strings `a` and `b`, `sort_key(a).cmp(&sort_key(b))` must equal the ordering of `a`
+    /// and `b` under the collation.
+    fn sort_key(&self, input: &str) -> Vec<u8>;
+
+    /// Encode the sort key of `input` into a fixed size plaintext.
+    ///
+    /// Sort keys longer than `N` bytes are truncated so strings whose keys share the first `N`
+    /// bytes compare as equal. Shorter keys are padded with zeros.
+    ///
+    /// Fails with [`OreError::InvalidSortKey`] if the sort key contains a zero byte (e.g. for a
+    /// string containing `'\0'`) as it couldn't be told apart from the padding.
+    fn encode<const N: usize>(&self, input: &str) -> Result<PlainText<N>, OreError> {
+        let key = self.sort_key(input);
+        if key.contains(&0) {
+            return Err(OreError::InvalidSortKey);
+        }
+
+        let mut output = [0; N];
+        let len = key.len().min(N);
+        output[..len].copy_from_slice(&key[..len]);
+        Ok(output)
+    }
+}
+
+/// Orders strings by their UTF-8 bytes (and so by code point), same as `str::cmp`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Binary;
+
+impl Collation for Binary {
+    fn sort_key(&self, input: &str) -> Vec<u8> {
+        input.as_bytes().to_vec()
+    }
+}
+
+/// Orders strings by code point after mapping every character to lowercase, so strings which only
+/// differ in case compare as equal.
+///
+/// This uses the locale-independent Unicode lowercase mapping (see [`char::to_lowercase`]). It is
+/// not Unicode case folding: for example `"STRASSE"` and `"straße"` don't compare as equal.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Lowercase;
+
+impl Collation for Lowercase {
+    fn sort_key(&self, input: &str) -> Vec<u8> {
+        input
+            .chars()
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+            .into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::bit2::OreAes128ChaCha20;
+    use crate::test_utils::init_ore;
+
+    quickcheck! {
+        fn binary_matches_str_order(x: String, y: String) -> bool {
+            Binary.sort_key(&x).cmp(&Binary.sort_key(&y)) == x.cmp(&y)
+        }
+
+        fn lowercase_matches_lowercase_order(x: String, y: String) -> bool {
+            let ore: OreAes128ChaCha20 = init_ore();
+            let a = ore.encrypt_var(&Lowercase.sort_key(&x), None).unwrap();
+            let b = ore.encrypt_var(&Lowercase.sort_key(&y), None).unwrap();
+
+            a.cmp(&b) == x.to_lowercase().cmp(&y.to_lowercase())
+        }
+    }
+
+    #[test]
+    fn lowercase_ordering() {
+        let mut names = vec!["bob", "Alice", "alice", "Charlie", "dave"];
+        names.sort_by_key(|name| Lowercase.sort_key(name));
+
+        assert_eq!(names, vec!["Alice", "alice", "bob", "Charlie", "dave"]);
+    }
+
+    #[test]
+    fn orders_by_code_point() {
+        /* Unlike a locale-aware collation, non-ASCII letters sort after every ASCII letter */
+        assert!(Lowercase.sort_key("Émile") > Lowercase.sort_key("Zoë"));
+        /* and lowercasing isn't case folding */
+        assert_ne!(Lowercase.sort_key("STRASSE"), Lowercase.sort_key("straße"));
+    }
+
+    #[test]
+    fn encode_pads_and_truncates() {
+        assert_eq!(Binary.encode::<4>("ab").unwrap(), [b'a', b'b', 0, 0]);
+        assert_eq!(Binary.encode::<4>("abcdef").unwrap(), *b"abcd");
+        assert_eq!(Lowercase.encode::<3>("ABC").unwrap(), *b"abc");
+    }
+
+    #[test]
+    fn encode_rejects_nul() {
+        assert!(matches!(
+            Binary.encode::<4>("a\0"),
+            Err(OreError::InvalidSortKey)
+        ));
+        assert!(matches!(
+            Lowercase.encode::<4>("\0"),
+            Err(OreError::InvalidSortKey)
+        ));
+    }
+
+    #[test]
+    fn encode_with_ore_encrypt() {
+        let ore: OreAes128ChaCha20 = init_ore();
+        let a = Lowercase
+            .encode::<8>("Apple")
+            .unwrap()
+            .encrypt(&ore)
+            .unwrap();
+        let b = Lowercase
+            .encode::<8>("apple")
+            .unwrap()
+            .encrypt(&ore)
+            .unwrap();
+        let c = Lowercase
+            .encode::<8>("APRICOT")
+            .unwrap()
+            .encrypt(&ore)
+            .unwrap();
+
+        assert!(a == b);
+        assert!(b < c);
+    }
+}
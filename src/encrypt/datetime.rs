@@ -0,0 +1,377 @@
+/*
+ * OreEncrypt for the date and time types of the chrono and time crates.
+ *
+ * Types from both crates share the same encodings so e.g. a chrono::NaiveDate and a time::Date
+ * for the same day produce comparable ciphertexts:
+ *
+ * - Dates are the number of days since 1970-01-01 as an i32 (4 blocks)
+ * - Timestamps are the number of whole seconds since 1970-01-01T00:00:00Z as an i64 followed by
+ *   the nanoseconds within the second as a u32 (12 blocks). Timestamps with an offset are
+ *   compared (and decrypted) in UTC.
+ * - Durations are the total number of nanoseconds as an i128 (16 blocks)
+ *
+ * Signed values use the same order-preserving mapping as signed integers so dates before 1970
+ * and negative durations order correctly.
+ */
+
+use super::*;
+use std::convert::TryFrom;
+
+const TIMESTAMP_SIZE: usize = 12;
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+
+fn encode_timestamp(secs: i64, nanos: u32) -> PlainText<TIMESTAMP_SIZE> {
+    encode_with(|out| {
+        let (secs_out, nanos_out) = out.split_at_mut(i64::SIZE);
+        secs.write_ordered(secs_out);
+        nanos.write_ordered(nanos_out);
+    })
+}
+
+fn decode_timestamp(input: &PlainText<TIMESTAMP_SIZE>) -> Result<(i64, u32), OreError> {
+    let (secs, nanos) = input.split_at(i64::SIZE);
+    Ok((i64::read_ordered(secs)?, u32::read_ordered(nanos)?))
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_types {
+    use super::*;
+    use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+
+    /* num_days_from_ce of 1970-01-01 */
+    const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+    impl OrePlaintext for NaiveDate {
+        type Encoded = <i32 as OrePlaintext>::Encoded;
+
+        fn encode(&self) -> Self::Encoded {
+            (self.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE).encode()
+        }
+
+        fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+            i32::decode(input)?
+                .checked_add(UNIX_EPOCH_DAYS_FROM_CE)
+                .and_then(NaiveDate::from_num_days_from_ce_opt)
+                .ok_or(OreError::DecryptFailed)
+        }
+    }
+
+    /* Leap seconds have nanoseconds above 1_000_000_000 so they still order correctly */
+    impl OrePlaintext for DateTime<Utc> {
+        type Encoded = PlainText<TIMESTAMP_SIZE>;
+
+        fn encode(&self) -> Self::Encoded {
+            encode_timestamp(self.timestamp(), self.timestamp_subsec_nanos())
+        }
+
+        fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+            let (secs, nanos) = decode_timestamp(input)?;
+            DateTime::from_timestamp(secs, nanos).ok_or(OreError::DecryptFailed)
+        }
+    }
+
+    /* Naive timestamps are encoded as if they were in UTC */
+    impl OrePlaintext for NaiveDateTime {
+        type Encoded = PlainText<TIMESTAMP_SIZE>;
+
+        fn encode(&self) -> Self::Encoded {
+            self.and_utc().encode()
+        }
+
+        fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+            DateTime::<Utc>::decode(input).map(|timestamp| timestamp.naive_utc())
+        }
+    }
+
+    impl OrePlaintext for TimeDelta {
+        type Encoded = <i128 as OrePlaintext>::Encoded;
+
+        fn encode(&self) -> Self::Encoded {
+            let nanos = (self.num_seconds() as i128) * NANOS_PER_SEC + self.subsec_nanos() as i128;
+            nanos.encode()
+        }
+
+        fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+            let nanos = i128::decode(input)?;
+            let secs = i64::try_from(nanos.div_euclid(NANOS_PER_SEC))
+                .map_err(|_| OreError::DecryptFailed)?;
+
+            TimeDelta::new(secs, nanos.rem_euclid(NANOS_PER_SEC) as u32)
+                .ok_or(OreError::DecryptFailed)
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_types {
+    use super::*;
+    use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime};
+
+    /* Julian day of 1970-01-01 */
+    const UNIX_EPOCH_JULIAN_DAY: i32 = 2_440_588;
+
+    impl OrePlaintext for Date {
+        type Encoded = <i32 as OrePlaintext>::Encoded;
+
+        fn encode(&self) -> Self::Encoded {
+            (self.to_julian_day() - UNIX_EPOCH_JULIAN_DAY).encode()
+        }
+
+        fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+            let days = i32::decode(input)?
+                .checked_add(UNIX_EPOCH_JULIAN_DAY)
+                .ok_or(OreError::DecryptFailed)?;
+
+            Date::from_julian_day(days).map_err(|_| OreError::DecryptFailed)
+        }
+    }
+
+    /* Decrypted timestamps are always in UTC */
+    impl OrePlaintext for OffsetDateTime {
+        type Encoded = PlainText<TIMESTAMP_SIZE>;
+
+        fn encode(&self) -> Self::Encoded {
+            encode_timestamp(self.unix_timestamp(), self.nanosecond())
+        }
+
+        fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+            let (secs, nanos) = decode_timestamp(input)?;
+
+            OffsetDateTime::from_unix_timestamp(secs)
+                .and_then(|timestamp| timestamp.replace_nanosecond(nanos))
+                .map_err(|_| OreError::DecryptFailed)
+        }
+    }
+
+    /* Primitive timestamps are encoded as if they were in UTC */
+    impl OrePlaintext for PrimitiveDateTime {
+        type Encoded = PlainText<TIMESTAMP_SIZE>;
+
+        fn encode(&self) -> Self::Encoded {
+            self.assume_utc().encode()
+        }
+
+        fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+            OffsetDateTime::decode(input)
+                .map(|timestamp| PrimitiveDateTime::new(timestamp.date(), timestamp.time()))
+        }
+    }
+
+    impl OrePlaintext for Duration {
+        type Encoded = <i128 as OrePlaintext>::Encoded;
+
+        fn encode(&self) -> Self::Encoded {
+            self.whole_nanoseconds().encode()
+        }
+
+        fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+            let nanos = i128::decode(input)?;
+            let secs = i64::try_from(nanos / NANOS_PER_SEC).map_err(|_| OreError::DecryptFailed)?;
+
+            Ok(Duration::new(secs, (nanos % NANOS_PER_SEC) as i32))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::bit2::OreAes128ChaCha20;
+    use crate::test_utils::init_ore;
+
+    type Ore = OreAes128ChaCha20;
+
+    /* Check that the ciphertexts of two values order the same way as the values */
+    fn ordered<P: Ord + OreEncrypt<Ore>>(x: &P, y: &P) -> bool
+    where
+        P::FullOutput: Ord,
+    {
+        let ore: Ore = init_ore();
+        let a = x.encrypt(&ore).unwrap();
+        let b = y.encrypt(&ore).unwrap();
+
+        a.cmp(&b) == x.cmp(y)
+    }
+
+    fn roundtrip<P: PartialEq + OreEncrypt<Ore>>(x: &P) -> bool {
+        let ore: Ore = init_ore();
+        let a = x.encrypt(&ore).unwrap();
+
+        P::decrypt(&a, &ore).unwrap() == *x
+    }
+
+    #[cfg(feature = "chrono")]
+    mod chrono_tests {
+        use super::*;
+        use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+
+        /* Days and seconds within +-10,000 years of the epoch */
+        fn date(days: i32) -> NaiveDate {
+            NaiveDate::from_num_days_from_ce_opt(days % 3_650_000 + 719_163).unwrap()
+        }
+
+        fn timestamp(secs: i64, nanos: u32) -> DateTime<Utc> {
+            DateTime::from_timestamp(secs % 315_000_000_000, nanos % 1_000_000_000).unwrap()
+        }
+
+        quickcheck! {
+            fn compare_naive_date(x: i32, y: i32) -> bool {
+                ordered(&date(x), &date(y))
+            }
+
+            fn compare_datetime(x: (i64, u32), y: (i64, u32)) -> bool {
+                ordered(&timestamp(x.0, x.1), &timestamp(y.0, y.1))
+            }
+
+            fn compare_time_delta(x: i64, y: i64) -> bool {
+                ordered(&TimeDelta::nanoseconds(x), &TimeDelta::nanoseconds(y))
+            }
+
+            fn roundtrip_naive_date(x: i32) -> bool {
+                roundtrip(&date(x))
+            }
+
+            fn roundtrip_naive_datetime(x: (i64, u32)) -> bool {
+                roundtrip(&timestamp(x.0, x.1).naive_utc())
+            }
+
+            fn roundtrip_time_delta(x: i64) -> bool {
+                roundtrip(&TimeDelta::microseconds(x))
+            }
+        }
+
+        #[test]
+        fn pre_epoch_dates() {
+            let ore: Ore = init_ore();
+            let a = NaiveDate::from_ymd_opt(-44, 3, 15)
+                .unwrap()
+                .encrypt(&ore)
+                .unwrap();
+            let b = NaiveDate::from_ymd_opt(1969, 12, 31)
+                .unwrap()
+                .encrypt(&ore)
+                .unwrap();
+            let c = NaiveDate::from_ymd_opt(1970, 1, 1)
+                .unwrap()
+                .encrypt(&ore)
+                .unwrap();
+
+            assert!(a < b);
+            assert!(b < c);
+        }
+
+        #[test]
+        fn leap_second() {
+            let leap = NaiveDate::from_ymd_opt(2016, 12, 31)
+                .and_then(|date| date.and_hms_nano_opt(23, 59, 59, 1_500_000_000))
+                .unwrap();
+            let before = leap - TimeDelta::milliseconds(600);
+            let after = NaiveDate::from_ymd_opt(2017, 1, 1)
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .unwrap();
+
+            assert!(ordered(&before, &leap));
+            assert!(ordered(&leap, &after));
+            assert!(roundtrip(&leap));
+        }
+
+        #[test]
+        fn nullable_date() {
+            let ore: Ore = init_ore();
+            let date = NaiveDateTime::MIN.encrypt(&ore).unwrap();
+            let null = NullsLast(None::<NaiveDateTime>).encrypt(&ore).unwrap();
+
+            assert_eq!(date.left.xt.len(), 12);
+            assert_eq!(null.left.xt.len(), 13);
+            assert!(roundtrip(&Some(NaiveDateTime::MAX)));
+        }
+    }
+
+    #[cfg(feature = "time")]
+    mod time_tests {
+        use super::*;
+        use time::{Date, Duration, OffsetDateTime, UtcOffset};
+
+        /* Days within +-8,000 years of the epoch (the default range of `time` is +-9,999 years) */
+        fn date(days: i32) -> Date {
+            Date::from_julian_day(days % 2_920_000 + 2_440_588).unwrap()
+        }
+
+        fn duration(secs: i64, nanos: i32) -> Duration {
+            Duration::new(secs, nanos % 1_000_000_000)
+        }
+
+        fn timestamp(secs: i64, nanos: u32, offset: i8) -> OffsetDateTime {
+            OffsetDateTime::from_unix_timestamp(secs % 250_000_000_000)
+                .and_then(|timestamp| timestamp.replace_nanosecond(nanos % 1_000_000_000))
+                .unwrap()
+                .to_offset(UtcOffset::from_hms(offset % 24, 0, 0).unwrap())
+        }
+
+        quickcheck! {
+            fn compare_date(x: i32, y: i32) -> bool {
+                ordered(&date(x), &date(y))
+            }
+
+            fn compare_offset_datetime(x: (i64, u32, i8), y: (i64, u32, i8)) -> bool {
+                ordered(&timestamp(x.0, x.1, x.2), &timestamp(y.0, y.1, y.2))
+            }
+
+            fn compare_duration(x: (i64, i32), y: (i64, i32)) -> bool {
+                ordered(&duration(x.0, x.1), &duration(y.0, y.1))
+            }
+
+            fn roundtrip_date(x: i32) -> bool {
+                roundtrip(&date(x))
+            }
+
+            fn roundtrip_offset_datetime(x: (i64, u32, i8)) -> bool {
+                roundtrip(&timestamp(x.0, x.1, x.2))
+            }
+
+            fn roundtrip_duration(x: (i64, i32)) -> bool {
+                roundtrip(&duration(x.0, x.1))
+            }
+        }
+
+        #[test]
+        fn duration_extremes() {
+            assert!(ordered(&Duration::MIN, &Duration::ZERO));
+            assert!(roundtrip(&Duration::MIN));
+            assert!(roundtrip(&Duration::MAX));
+        }
+
+        #[test]
+        fn decrypts_to_utc() {
+            let ore: Ore = init_ore();
+            let local = timestamp(1_000_000, 0, 10);
+            let a = local.encrypt(&ore).unwrap();
+            let decrypted = OffsetDateTime::decrypt(&a, &ore).unwrap();
+
+            assert_eq!(decrypted, local);
+            assert_eq!(decrypted.offset(), UtcOffset::UTC);
+        }
+    }
+
+    #[cfg(all(feature = "chrono", feature = "time"))]
+    #[test]
+    fn chrono_and_time_are_compatible() {
+        let ore: Ore = init_ore();
+        let a = chrono::NaiveDate::from_ymd_opt(1900, 2, 3)
+            .unwrap()
+            .encrypt(&ore)
+            .unwrap();
+        let b = time::Date::from_calendar_date(1900, time::Month::February, 3)
+            .unwrap()
+            .encrypt(&ore)
+            .unwrap();
+        assert_eq!(a.cmp(&b), std::cmp::Ordering::Equal);
+
+        let a = chrono::DateTime::from_timestamp(-1_234_567, 890).unwrap();
+        let b =
+            time::OffsetDateTime::from_unix_timestamp_nanos(-1_234_567_000_000_000 + 890).unwrap();
+        let a = a.encrypt_left(&ore).unwrap();
+        let b = b.encrypt_left(&ore).unwrap();
+        assert_eq!(ore.decrypt_left(&a).unwrap(), ore.decrypt_left(&b).unwrap());
+    }
+}
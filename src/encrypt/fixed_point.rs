@@ -0,0 +1,452 @@
+/*
+ * Fixed-point numbers are encoded as the number of units of 10^-SCALE (e.g. cents for a scale of
+ * 2) as an i128 (16 blocks), using the same order-preserving mapping as signed integers.
+ *
+ * Decimals are encoded the same way after scaling them to the scale of the encoding, so a
+ * FixedPoint<2> and a Decimal encoded with a scale of 2 produce comparable ciphertexts. Unlike
+ * floats, every value in range is encoded exactly.
+ */
+
+use super::*;
+
+/// A fixed-point number stored as an integer number of units of `10^-SCALE`.
+///
+/// Ciphertexts order by value and have 16 blocks. For example, an amount of money in cents is a
+/// `FixedPoint<2>`:
+///
+/// ```rust
+/// # use ore_rs::{
+/// #     FixedPoint,
+/// #     OreCipher,  // Main ORE Cipher trait
+/// #     OreEncrypt, // Traits for encrypting primitive types (e.g. u64)
+/// #     scheme::bit2::OreAes128ChaCha20 // Specific scheme we want to use
+/// # };
+/// # use hex_literal::hex;
+/// # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+/// let price = FixedPoint::<2>(1999); // 19.99
+/// let refund = FixedPoint::<2>(-500); // -5.00
+///
+/// let a = price.encrypt(&ore).unwrap();
+/// let b = refund.encrypt(&ore).unwrap();
+/// assert!(a > b);
+/// ```
+///
+/// With the `rust_decimal` feature, `Decimal` values convert to and from `FixedPoint` with
+/// `TryFrom` as long as they can be represented exactly.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FixedPoint<const SCALE: u32>(pub i128);
+
+impl<const SCALE: u32> OrePlaintext for FixedPoint<SCALE> {
+    type Encoded = <i128 as OrePlaintext>::Encoded;
+
+    fn encode(&self) -> Self::Encoded {
+        self.0.encode()
+    }
+
+    fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+        i128::decode(input).map(FixedPoint)
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+pub use self::decimal::{DecimalEncoding, Scaled};
+
+#[cfg(feature = "rust_decimal")]
+mod decimal {
+    use super::*;
+    use rust_decimal::Decimal;
+    use std::convert::TryFrom;
+
+    /* The largest scale of a Decimal */
+    const MAX_DECIMAL_SCALE: u32 = 28;
+
+    /// An order-preserving encoding of [`Decimal`] values with a fixed precision and scale, like
+    /// the `NUMERIC(precision, scale)` type of SQL databases.
+    ///
+    /// Values are encoded as a 16 byte plaintext so they encrypt to ciphertexts with 16 blocks
+    /// which are comparable with the ciphertexts of a [`FixedPoint`] with the same scale.
+    ///
+    /// ```rust
+    /// # use ore_rs::{
+    /// #     DecimalEncoding,
+    /// #     OreCipher,  // Main ORE Cipher trait
+    /// #     OreEncrypt, // Traits for encrypting primitive types (e.g. u64)
+    /// #     scheme::bit2::OreAes128ChaCha20 // Specific scheme we want to use
+    /// # };
+    /// # use hex_literal::hex;
+    /// use rust_decimal::Decimal;
+    /// # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+    /// # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+    /// # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+    ///
+    /// // Up to 18 digits, 2 of them after the decimal point
+    /// const PRICE: DecimalEncoding = DecimalEncoding::new(18, 2);
+    ///
+    /// let a = PRICE.encode(&Decimal::new(1999, 2)).unwrap().encrypt(&ore).unwrap();
+    /// let b = PRICE.encode(&Decimal::new(20, 0)).unwrap().encrypt(&ore).unwrap();
+    /// assert!(a < b);
+    ///
+    /// let decrypted = <[u8; 16]>::decrypt(&a, &ore).unwrap();
+    /// assert_eq!(PRICE.decode(&decrypted).unwrap(), Decimal::new(1999, 2));
+    ///
+    /// // Values with more fractional digits than the scale can't be encoded exactly
+    /// assert!(PRICE.encode(&Decimal::new(19999, 3)).is_err());
+    /// ```
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct DecimalEncoding {
+        precision: u32,
+        scale: u32,
+    }
+
+    impl DecimalEncoding {
+        /// The largest supported precision. Every 38 digit number fits into an `i128`.
+        pub const MAX_PRECISION: u32 = 38;
+
+        /// An encoding of decimals with at most `precision` significant digits, `scale` of which
+        /// are after the decimal point.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `precision` is 0 or above [`DecimalEncoding::MAX_PRECISION`], or if `scale`
+        /// is above `precision` or the maximum scale of a `Decimal` (28).
+        pub const fn new(precision: u32, scale: u32) -> Self {
+            assert!(precision > 0 && precision <= Self::MAX_PRECISION);
+            assert!(scale <= precision && scale <= MAX_DECIMAL_SCALE);
+
+            Self { precision, scale }
+        }
+
+        pub fn precision(&self) -> u32 {
+            self.precision
+        }
+
+        pub fn scale(&self) -> u32 {
+            self.scale
+        }
+
+        /// Encode a value into a plaintext which can be encrypted with
+        /// [`OreEncrypt`](crate::OreEncrypt).
+        ///
+        /// Fails with [`OreError::InvalidDecimal`] if the value has more fractional digits than
+        /// the scale (ignoring trailing zeros) or more digits than the precision.
+        pub fn encode(&self, input: &Decimal) -> Result<PlainText<16>, OreError> {
+            to_units(input, self.scale)
+                .filter(|units| self.in_range(*units))
+                .map(|units| units.encode())
+                .ok_or(OreError::InvalidDecimal)
+        }
+
+        /// Decode a value from a plaintext produced by [`DecimalEncoding::encode`].
+        ///
+        /// Fails with [`OreError::InvalidDecimal`] if the plaintext is out of range for this
+        /// encoding.
+        pub fn decode(&self, input: &PlainText<16>) -> Result<Decimal, OreError> {
+            let units = i128::decode(input)?;
+
+            if !self.in_range(units) {
+                return Err(OreError::InvalidDecimal);
+            }
+
+            from_units(units, self.scale).ok_or(OreError::InvalidDecimal)
+        }
+
+        fn in_range(&self, units: i128) -> bool {
+            units.unsigned_abs() < 10u128.pow(self.precision)
+        }
+    }
+
+    /// A [`Decimal`] which encrypts with the [`DecimalEncoding`] of the given `PRECISION` and
+    /// `SCALE`, like a column of type `NUMERIC(PRECISION, SCALE)`.
+    ///
+    /// Values are checked against the encoding when they're created so they can be encrypted
+    /// with [`OreEncrypt`](crate::OreEncrypt) directly and used as fields of structs deriving
+    /// `OreEncrypt`.
+    ///
+    /// ```rust
+    /// # use ore_rs::{
+    /// #     OreCipher,  // Main ORE Cipher trait
+    /// #     OreEncrypt, // Traits for encrypting primitive types (e.g. u64)
+    /// #     Scaled,
+    /// #     scheme::bit2::OreAes128ChaCha20 // Specific scheme we want to use
+    /// # };
+    /// # use hex_literal::hex;
+    /// use rust_decimal::Decimal;
+    /// # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+    /// # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+    /// # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+    ///
+    /// type Price = Scaled<18, 2>;
+    ///
+    /// let a = Price::new(Decimal::new(1999, 2)).unwrap().encrypt(&ore).unwrap();
+    /// let b = Price::new(Decimal::new(20, 0)).unwrap().encrypt(&ore).unwrap();
+    /// assert!(a < b);
+    /// assert_eq!(Price::decrypt(&a, &ore).unwrap().get(), Decimal::new(1999, 2));
+    ///
+    /// assert!(Price::new(Decimal::new(19999, 3)).is_err());
+    /// ```
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Scaled<const PRECISION: u32, const SCALE: u32>(Decimal);
+
+    impl<const PRECISION: u32, const SCALE: u32> Scaled<PRECISION, SCALE> {
+        /// The encoding used for values. Using a `Scaled` with an invalid precision or scale
+        /// (see [`DecimalEncoding::new`]) fails to compile.
+        pub const ENCODING: DecimalEncoding = DecimalEncoding::new(PRECISION, SCALE);
+
+        /// Fails with [`OreError::InvalidDecimal`] if the value can't be encoded exactly.
+        pub fn new(value: Decimal) -> Result<Self, OreError> {
+            Self::ENCODING.encode(&value).map(|_| Self(value))
+        }
+
+        pub fn get(&self) -> Decimal {
+            self.0
+        }
+    }
+
+    impl<const PRECISION: u32, const SCALE: u32> TryFrom<Decimal> for Scaled<PRECISION, SCALE> {
+        type Error = OreError;
+
+        fn try_from(value: Decimal) -> Result<Self, Self::Error> {
+            Self::new(value)
+        }
+    }
+
+    impl<const PRECISION: u32, const SCALE: u32> From<Scaled<PRECISION, SCALE>> for Decimal {
+        fn from(value: Scaled<PRECISION, SCALE>) -> Self {
+            value.0
+        }
+    }
+
+    impl<const PRECISION: u32, const SCALE: u32> OrePlaintext for Scaled<PRECISION, SCALE> {
+        type Encoded = PlainText<16>;
+
+        fn encode(&self) -> Self::Encoded {
+            Self::ENCODING
+                .encode(&self.0)
+                .expect("values are checked when they are created")
+        }
+
+        fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+            Self::ENCODING.decode(input).map(Self)
+        }
+    }
+
+    /* The value as a number of units of 10^-scale if that can be represented exactly */
+    fn to_units(input: &Decimal, scale: u32) -> Option<i128> {
+        let mantissa = input.mantissa();
+
+        if input.scale() <= scale {
+            10i128
+                .checked_pow(scale - input.scale())
+                .and_then(|factor| mantissa.checked_mul(factor))
+        } else {
+            let divisor = 10i128.checked_pow(input.scale() - scale)?;
+            if mantissa % divisor == 0 {
+                Some(mantissa / divisor)
+            } else {
+                None
+            }
+        }
+    }
+
+    fn from_units(units: i128, scale: u32) -> Option<Decimal> {
+        Decimal::try_from_i128_with_scale(units, scale).ok()
+    }
+
+    impl<const SCALE: u32> TryFrom<Decimal> for FixedPoint<SCALE> {
+        type Error = OreError;
+
+        fn try_from(value: Decimal) -> Result<Self, Self::Error> {
+            to_units(&value, SCALE)
+                .map(FixedPoint)
+                .ok_or(OreError::InvalidDecimal)
+        }
+    }
+
+    impl<const SCALE: u32> TryFrom<FixedPoint<SCALE>> for Decimal {
+        type Error = OreError;
+
+        fn try_from(value: FixedPoint<SCALE>) -> Result<Self, Self::Error> {
+            from_units(value.0, SCALE).ok_or(OreError::InvalidDecimal)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::bit2::OreAes128ChaCha20;
+    use crate::test_utils::init_ore;
+
+    type Ore = OreAes128ChaCha20;
+
+    quickcheck! {
+        fn compare_fixed_point(x: i128, y: i128) -> bool {
+            let ore: Ore = init_ore();
+            let a = FixedPoint::<2>(x).encrypt(&ore).unwrap();
+            let b = FixedPoint::<2>(y).encrypt(&ore).unwrap();
+
+            a.cmp(&b) == x.cmp(&y)
+        }
+
+        fn roundtrip_fixed_point(x: i128) -> bool {
+            let ore: Ore = init_ore();
+            let a = FixedPoint::<4>(x).encrypt(&ore).unwrap();
+
+            FixedPoint::<4>::decrypt(&a, &ore).unwrap() == FixedPoint(x)
+        }
+
+        fn compare_nullable_fixed_point(x: Option<i128>, y: Option<i128>) -> bool {
+            let ore: Ore = init_ore();
+            let a = x.map(FixedPoint::<2>).encrypt(&ore).unwrap();
+            let b = y.map(FixedPoint::<2>).encrypt(&ore).unwrap();
+
+            Option::<FixedPoint<2>>::decrypt(&a, &ore).unwrap() == x.map(FixedPoint)
+                && a.cmp(&b) == x.cmp(&y)
+        }
+    }
+
+    #[test]
+    fn fixed_point_matches_i128() {
+        let ore: Ore = init_ore();
+        let a = FixedPoint::<2>(-1999).encrypt_left(&ore).unwrap();
+        let b = (-1999i128).encrypt_left(&ore).unwrap();
+
+        assert_eq!(ore.decrypt_left(&a).unwrap(), ore.decrypt_left(&b).unwrap());
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    mod decimal_tests {
+        use super::*;
+        use rust_decimal::Decimal;
+        use std::convert::TryFrom;
+
+        const MONEY: DecimalEncoding = DecimalEncoding::new(18, 4);
+
+        /* A decimal with at most 14 digits and a scale of at most 4 which fits MONEY */
+        fn decimal(mantissa: i64, scale: u8) -> Decimal {
+            Decimal::new(mantissa % 100_000_000_000_000, (scale % 5) as u32)
+        }
+
+        quickcheck! {
+            fn compare_decimal(x: (i64, u8), y: (i64, u8)) -> bool {
+                let ore: Ore = init_ore();
+                let (x, y) = (decimal(x.0, x.1), decimal(y.0, y.1));
+                let a = MONEY.encode(&x).unwrap().encrypt(&ore).unwrap();
+                let b = MONEY.encode(&y).unwrap().encrypt(&ore).unwrap();
+
+                a.cmp(&b) == x.cmp(&y)
+            }
+
+            fn roundtrip_decimal(x: (i64, u8)) -> bool {
+                let ore: Ore = init_ore();
+                let x = decimal(x.0, x.1);
+                let a = MONEY.encode(&x).unwrap().encrypt(&ore).unwrap();
+                let plaintext = <[u8; 16]>::decrypt(&a, &ore).unwrap();
+
+                MONEY.decode(&plaintext).unwrap() == x
+            }
+        }
+
+        #[test]
+        fn trailing_zeros_are_ignored() {
+            let encoding = DecimalEncoding::new(10, 2);
+
+            assert_eq!(
+                encoding.encode(&Decimal::new(1_500, 3)).unwrap(),
+                encoding.encode(&Decimal::new(15, 1)).unwrap()
+            );
+            assert!(encoding.encode(&Decimal::new(1_505, 3)).is_err());
+        }
+
+        #[test]
+        fn precision_limits_the_range() {
+            let encoding = DecimalEncoding::new(5, 2);
+
+            assert!(encoding.encode(&Decimal::new(99_999, 2)).is_ok());
+            assert!(encoding.encode(&Decimal::new(-99_999, 2)).is_ok());
+            assert!(matches!(
+                encoding.encode(&Decimal::new(100_000, 2)),
+                Err(OreError::InvalidDecimal)
+            ));
+            assert!(matches!(
+                encoding.decode(&MONEY.encode(&Decimal::new(1_000, 0)).unwrap()),
+                Err(OreError::InvalidDecimal)
+            ));
+        }
+
+        #[test]
+        fn extreme_decimals() {
+            let encoding = DecimalEncoding::new(38, 0);
+            let ore: Ore = init_ore();
+            let a = encoding
+                .encode(&Decimal::MIN)
+                .unwrap()
+                .encrypt(&ore)
+                .unwrap();
+            let b = encoding
+                .encode(&Decimal::MAX)
+                .unwrap()
+                .encrypt(&ore)
+                .unwrap();
+
+            assert!(a < b);
+            assert!(matches!(
+                DecimalEncoding::new(38, 10).encode(&Decimal::MAX),
+                Err(OreError::InvalidDecimal)
+            ));
+        }
+
+        quickcheck! {
+            fn compare_scaled(x: (i64, u8), y: (i64, u8)) -> bool {
+                let ore: Ore = init_ore();
+                let (x, y) = (decimal(x.0, x.1), decimal(y.0, y.1));
+                let a = Scaled::<18, 4>::new(x).unwrap().encrypt(&ore).unwrap();
+                let b = Scaled::<18, 4>::new(y).unwrap().encrypt_left(&ore).unwrap();
+
+                b.partial_cmp(&a) == Some(y.cmp(&x))
+                    && Scaled::<18, 4>::decrypt(&a, &ore).unwrap().get() == x
+            }
+        }
+
+        #[test]
+        fn scaled_matches_encoding() {
+            let value = Decimal::new(-1999, 2);
+
+            assert_eq!(
+                Scaled::<18, 4>::new(value).unwrap().encode(),
+                MONEY.encode(&value).unwrap()
+            );
+            assert!(matches!(
+                Scaled::<5, 2>::new(Decimal::new(100_000, 2)),
+                Err(OreError::InvalidDecimal)
+            ));
+            assert!(matches!(
+                Scaled::<5, 2>::decode(&MONEY.encode(&Decimal::new(1_000, 0)).unwrap()),
+                Err(OreError::InvalidDecimal)
+            ));
+            assert!(Scaled::<4, 1>::try_from(value).is_err());
+        }
+
+        #[test]
+        fn decimal_matches_fixed_point() {
+            let ore: Ore = init_ore();
+            let value = Decimal::new(-1999, 2);
+            let a = MONEY.encode(&value).unwrap().encrypt(&ore).unwrap();
+            let b = FixedPoint::<4>::try_from(value)
+                .unwrap()
+                .encrypt(&ore)
+                .unwrap();
+
+            assert_eq!(ore.decrypt(&a).unwrap(), ore.decrypt(&b).unwrap());
+            assert_eq!(
+                FixedPoint::<4>::try_from(value).unwrap(),
+                FixedPoint(-199_900)
+            );
+            assert_eq!(Decimal::try_from(FixedPoint::<2>(-1999)).unwrap(), value);
+            assert!(FixedPoint::<1>::try_from(value).is_err());
+            assert!(Decimal::try_from(FixedPoint::<0>(i128::MAX)).is_err());
+        }
+    }
+}
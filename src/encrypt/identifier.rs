@@ -0,0 +1,171 @@
+/*
+ * OreEncrypt for 128-bit identifiers from the uuid and ulid crates.
+ *
+ * Identifiers are encoded as their 16 bytes (16 blocks) in the same order as their canonical
+ * string form, so ciphertexts order the same way as the identifiers do. For time-ordered
+ * identifiers (UUIDv7 and ULIDs) the timestamp comes first which means ciphertexts can be range
+ * scanned by creation time.
+ */
+
+use super::*;
+
+mod uuid_types {
+    use super::*;
+    use uuid::Uuid;
+
+    impl OrePlaintext for Uuid {
+        type Encoded = PlainText<16>;
+
+        fn encode(&self) -> Self::Encoded {
+            *self.as_bytes()
+        }
+
+        fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+            Ok(Uuid::from_bytes(*input))
+        }
+    }
+}
+
+mod ulid_types {
+    use super::*;
+    use ulid::Ulid;
+
+    impl OrePlaintext for Ulid {
+        type Encoded = <u128 as OrePlaintext>::Encoded;
+
+        fn encode(&self) -> Self::Encoded {
+            self.0.encode()
+        }
+
+        fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+            u128::decode(input).map(Ulid)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::bit2::OreAes128ChaCha20;
+    use crate::test_utils::init_ore;
+    use crate::OreOutput;
+
+    type Ore = OreAes128ChaCha20;
+
+    /* Encrypt, serialize, parse and decrypt a value */
+    fn roundtrip<P: PartialEq + OreEncrypt<Ore, FullOutput = CipherText<Ore, 16>>>(x: &P) -> bool {
+        let ore: Ore = init_ore();
+        let bytes = x.encrypt(&ore).unwrap().to_bytes();
+        let parsed = CipherText::<Ore, 16>::from_slice(&bytes).unwrap();
+
+        P::decrypt(&parsed, &ore).unwrap() == *x
+    }
+
+    mod uuid_tests {
+        use super::*;
+        use uuid::{Builder, Uuid};
+
+        fn uuid_v7(millis: u64, random: u128) -> Uuid {
+            let mut random_bytes = [0; 10];
+            random_bytes.copy_from_slice(&random.to_be_bytes()[6..]);
+
+            Builder::from_unix_timestamp_millis(millis % (1 << 48), &random_bytes).into_uuid()
+        }
+
+        quickcheck! {
+            fn compare_uuid(x: u128, y: u128) -> bool {
+                let ore: Ore = init_ore();
+                let (x, y) = (Uuid::from_u128(x), Uuid::from_u128(y));
+                let a = x.encrypt(&ore).unwrap();
+                let b = y.encrypt(&ore).unwrap();
+
+                a.cmp(&b) == x.cmp(&y)
+            }
+
+            fn roundtrip_uuid(x: u128) -> bool {
+                roundtrip(&Uuid::from_u128(x))
+            }
+
+            fn roundtrip_uuid_v7(millis: u64, random: u128) -> bool {
+                roundtrip(&uuid_v7(millis, random))
+            }
+        }
+
+        #[test]
+        fn uuid_v7_range_scan() {
+            let ore: Ore = init_ore();
+            let stored: Vec<_> = (0..10)
+                .map(|i| uuid_v7(1_700_000_000_000 + i * 1_000, u128::MAX - i as u128))
+                .map(|uuid| uuid.encrypt(&ore).unwrap())
+                .collect();
+
+            /* Everything created between the 3rd and the 7th second */
+            let lo = uuid_v7(1_700_000_003_000, 0).encrypt_left(&ore).unwrap();
+            let hi = uuid_v7(1_700_000_007_000, u128::MAX)
+                .encrypt_left(&ore)
+                .unwrap();
+            let matches = stored.iter().filter(|ct| lo <= **ct && hi >= **ct).count();
+
+            assert_eq!(matches, 5);
+        }
+
+        #[test]
+        fn nullable_uuid() {
+            assert!(roundtrip_nullable(Some(Uuid::from_u128(u128::MAX))));
+            assert!(roundtrip_nullable(None::<Uuid>));
+        }
+
+        fn roundtrip_nullable(x: Option<Uuid>) -> bool {
+            let ore: Ore = init_ore();
+            let bytes = x.encrypt(&ore).unwrap().to_bytes();
+            let parsed = CipherText::<Ore, 17>::from_slice(&bytes).unwrap();
+
+            Option::<Uuid>::decrypt(&parsed, &ore).unwrap() == x
+        }
+    }
+
+    mod ulid_tests {
+        use super::*;
+        use ulid::Ulid;
+
+        quickcheck! {
+            fn compare_ulid(x: u128, y: u128) -> bool {
+                let ore: Ore = init_ore();
+                let (x, y) = (Ulid(x), Ulid(y));
+                let a = x.encrypt(&ore).unwrap();
+                let b = y.encrypt(&ore).unwrap();
+
+                a.cmp(&b) == x.cmp(&y)
+            }
+
+            fn roundtrip_ulid(x: u128) -> bool {
+                roundtrip(&Ulid(x))
+            }
+        }
+
+        #[test]
+        fn ulid_orders_by_time() {
+            let ore: Ore = init_ore();
+            let earlier = Ulid::from_parts(1_700_000_000_000, u128::MAX)
+                .encrypt(&ore)
+                .unwrap();
+            let later = Ulid::from_parts(1_700_000_000_001, 0)
+                .encrypt(&ore)
+                .unwrap();
+
+            assert!(earlier < later);
+        }
+    }
+
+    #[test]
+    fn ulid_and_uuid_are_compatible() {
+        let ore: Ore = init_ore();
+        let ulid = ulid::Ulid::from_parts(1_700_000_000_000, 42);
+        let a = ulid.encrypt_left(&ore).unwrap();
+        let b = uuid::Uuid::from_bytes(ulid.to_bytes())
+            .encrypt_left(&ore)
+            .unwrap();
+
+        assert_eq!(ore.decrypt_left(&a).unwrap(), ore.decrypt_left(&b).unwrap());
+    }
+}
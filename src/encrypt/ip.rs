@@ -0,0 +1,350 @@
+/*
+ * IP addresses are encoded as their octets in network byte order, i.e. as a big endian u32 for
+ * IPv4 (4 blocks) and a u128 for IPv6 (16 blocks), so ciphertexts order the same way as the
+ * addresses do numerically.
+ *
+ * An IpAddr can hold either version so it's always encoded with 16 blocks, with IPv4 addresses
+ * mapped to IPv6 (::ffff:a.b.c.d). IPv4-mapped addresses decrypt back to IPv4 addresses.
+ * Note that this differs from the Ord impl of IpAddr which sorts every IPv4 address before every
+ * IPv6 address.
+ */
+
+use super::*;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+impl OrePlaintext for Ipv4Addr {
+    type Encoded = PlainText<4>;
+
+    fn encode(&self) -> Self::Encoded {
+        self.octets()
+    }
+
+    fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+        Ok(Ipv4Addr::from(*input))
+    }
+}
+
+impl OrePlaintext for Ipv6Addr {
+    type Encoded = PlainText<16>;
+
+    fn encode(&self) -> Self::Encoded {
+        self.octets()
+    }
+
+    fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+        Ok(Ipv6Addr::from(*input))
+    }
+}
+
+impl OrePlaintext for IpAddr {
+    type Encoded = PlainText<16>;
+
+    fn encode(&self) -> Self::Encoded {
+        match self {
+            IpAddr::V4(addr) => addr.to_ipv6_mapped().encode(),
+            IpAddr::V6(addr) => addr.encode(),
+        }
+    }
+
+    fn decode(input: &Self::Encoded) -> Result<Self, OreError> {
+        let addr = Ipv6Addr::from(*input);
+
+        Ok(addr
+            .to_ipv4_mapped()
+            .map(IpAddr::V4)
+            .unwrap_or(IpAddr::V6(addr)))
+    }
+}
+
+/// A CIDR block of IP addresses such as `10.0.0.0/8`.
+///
+/// [`Cidr::encrypt_bounds`] returns [`Left`] ciphertexts of the first and last address of the
+/// block which can be compared with encrypted addresses to check if they are in the block.
+///
+/// ```rust
+/// # use ore_rs::{
+/// #     Cidr,
+/// #     OreCipher,  // Main ORE Cipher trait
+/// #     OreEncrypt, // Traits for encrypting primitive types (e.g. u64)
+/// #     scheme::bit2::OreAes128ChaCha20 // Specific scheme we want to use
+/// # };
+/// # use hex_literal::hex;
+/// use std::net::Ipv4Addr;
+/// # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+///
+/// let stored = Ipv4Addr::new(10, 1, 2, 3).encrypt(&ore).unwrap();
+///
+/// let block = Cidr::<Ipv4Addr>::new(Ipv4Addr::new(10, 0, 0, 0), 8).unwrap();
+/// let (lo, hi) = block.encrypt_bounds(&ore).unwrap();
+/// assert!(lo <= stored && hi >= stored);
+///
+/// let block = Cidr::<Ipv4Addr>::new(Ipv4Addr::new(192, 168, 0, 0), 16).unwrap();
+/// let (lo, hi) = block.encrypt_bounds(&ore).unwrap();
+/// assert!(!(lo <= stored && hi >= stored));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cidr<A> {
+    network: A,
+    prefix_len: u8,
+}
+
+impl<A: Copy> Cidr<A> {
+    /// The address the block was created with. Host bits are not cleared.
+    pub fn network(&self) -> A {
+        self.network
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+}
+
+macro_rules! impl_cidr {
+    ($addr:ty, $bits:ty, $size:literal) => {
+        impl Cidr<$addr> {
+            /// Create a block from an address and a prefix length. Bits of `network` after the
+            /// prefix are ignored.
+            ///
+            /// Fails with [`OreError::InvalidCidr`] if the prefix is longer than the address.
+            pub fn new(network: $addr, prefix_len: u8) -> Result<Self, OreError> {
+                if u32::from(prefix_len) > <$bits>::BITS {
+                    return Err(OreError::InvalidCidr);
+                }
+
+                Ok(Self {
+                    network,
+                    prefix_len,
+                })
+            }
+
+            /* The bits of the addresses that vary within the block */
+            fn host_mask(&self) -> $bits {
+                <$bits>::MAX
+                    .checked_shr(u32::from(self.prefix_len))
+                    .unwrap_or(0)
+            }
+
+            /// The first (lowest) address in the block.
+            pub fn first(&self) -> $addr {
+                <$addr>::from(<$bits>::from(self.network) & !self.host_mask())
+            }
+
+            /// The last (highest) address in the block.
+            pub fn last(&self) -> $addr {
+                <$addr>::from(<$bits>::from(self.network) | self.host_mask())
+            }
+
+            /// Encrypt the first and last address of the block as an inclusive pair of bounds.
+            pub fn encrypt_bounds<T: OreCipher>(
+                &self,
+                cipher: &T,
+            ) -> Result<(Left<T, $size>, Left<T, $size>), OreError> {
+                Ok((
+                    self.first().encrypt_left(cipher)?,
+                    self.last().encrypt_left(cipher)?,
+                ))
+            }
+        }
+    };
+}
+
+impl_cidr!(Ipv4Addr, u32, 4);
+impl_cidr!(Ipv6Addr, u128, 16);
+
+/* Blocks of either version delegate to the block of the same version */
+impl Cidr<IpAddr> {
+    /// Create a block from an address and a prefix length. Bits of `network` after the prefix
+    /// are ignored.
+    ///
+    /// Fails with [`OreError::InvalidCidr`] if the prefix is longer than the address.
+    pub fn new(network: IpAddr, prefix_len: u8) -> Result<Self, OreError> {
+        let max_prefix_len = match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+
+        if prefix_len > max_prefix_len {
+            return Err(OreError::InvalidCidr);
+        }
+
+        Ok(Self {
+            network,
+            prefix_len,
+        })
+    }
+
+    /// The first (lowest) address in the block.
+    pub fn first(&self) -> IpAddr {
+        match self.network {
+            IpAddr::V4(network) => IpAddr::V4(self.with_network(network).first()),
+            IpAddr::V6(network) => IpAddr::V6(self.with_network(network).first()),
+        }
+    }
+
+    /// The last (highest) address in the block.
+    pub fn last(&self) -> IpAddr {
+        match self.network {
+            IpAddr::V4(network) => IpAddr::V4(self.with_network(network).last()),
+            IpAddr::V6(network) => IpAddr::V6(self.with_network(network).last()),
+        }
+    }
+
+    /// Encrypt the first and last address of the block as an inclusive pair of bounds.
+    pub fn encrypt_bounds<T: OreCipher>(
+        &self,
+        cipher: &T,
+    ) -> Result<(Left<T, 16>, Left<T, 16>), OreError> {
+        Ok((
+            self.first().encrypt_left(cipher)?,
+            self.last().encrypt_left(cipher)?,
+        ))
+    }
+
+    fn with_network<A>(&self, network: A) -> Cidr<A> {
+        Cidr {
+            network,
+            prefix_len: self.prefix_len,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::bit2::OreAes128ChaCha20;
+    use crate::test_utils::init_ore;
+
+    type Ore = OreAes128ChaCha20;
+
+    /* The value of an address in the order of the encoding */
+    fn ordinal(addr: IpAddr) -> u128 {
+        match addr {
+            IpAddr::V4(addr) => u128::from(addr.to_ipv6_mapped()),
+            IpAddr::V6(addr) => u128::from(addr),
+        }
+    }
+
+    /* Mix IPv4 and IPv6 addresses */
+    fn ip_addr(x: u128, v4: bool) -> IpAddr {
+        if v4 {
+            IpAddr::V4(Ipv4Addr::from(x as u32))
+        } else {
+            IpAddr::V6(Ipv6Addr::from(x))
+        }
+    }
+
+    quickcheck! {
+        fn compare_ip_addr(x: (u128, bool), y: (u128, bool)) -> bool {
+            let ore: Ore = init_ore();
+            let (x, y) = (ip_addr(x.0, x.1), ip_addr(y.0, y.1));
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            a.cmp(&b) == ordinal(x).cmp(&ordinal(y))
+        }
+
+        fn compare_ipv4(x: u32, y: u32) -> bool {
+            let ore: Ore = init_ore();
+            let (x, y) = (Ipv4Addr::from(x), Ipv4Addr::from(y));
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            a.cmp(&b) == x.cmp(&y)
+        }
+
+        fn compare_ipv6(x: u128, y: u128) -> bool {
+            let ore: Ore = init_ore();
+            let (x, y) = (Ipv6Addr::from(x), Ipv6Addr::from(y));
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            a.cmp(&b) == x.cmp(&y)
+        }
+
+        fn roundtrip_ip_addr(x: u128, v4: bool) -> bool {
+            let ore: Ore = init_ore();
+            let x = ip_addr(x, v4);
+            let a = x.encrypt(&ore).unwrap();
+
+            IpAddr::decrypt(&a, &ore).unwrap() == x
+        }
+
+        fn ipv4_cidr_contains(x: u32, network: u32, prefix_len: u8) -> bool {
+            let ore: Ore = init_ore();
+            let block = Cidr::<Ipv4Addr>::new(Ipv4Addr::from(network), prefix_len % 33).unwrap();
+            let (lo, hi) = block.encrypt_bounds(&ore).unwrap();
+            let stored = Ipv4Addr::from(x).encrypt(&ore).unwrap();
+
+            let contains = x & !block.host_mask() == network & !block.host_mask();
+            (lo <= stored && hi >= stored) == contains
+        }
+
+        fn ip_addr_cidr_contains(x: (u128, bool), network: (u128, bool), prefix_len: u8) -> bool {
+            let ore: Ore = init_ore();
+            let (x, network) = (ip_addr(x.0, x.1), ip_addr(network.0, network.1));
+            let max_prefix_len = if network.is_ipv4() { 33 } else { 129 };
+            let block = Cidr::<IpAddr>::new(network, prefix_len % max_prefix_len).unwrap();
+            let (lo, hi) = block.encrypt_bounds(&ore).unwrap();
+            let stored = x.encrypt(&ore).unwrap();
+
+            let contains = ordinal(block.first()) <= ordinal(x) && ordinal(x) <= ordinal(block.last());
+            (lo <= stored && hi >= stored) == contains
+        }
+    }
+
+    #[test]
+    fn cidr_first_and_last() {
+        let block = Cidr::<Ipv4Addr>::new(Ipv4Addr::new(10, 1, 2, 3), 8).unwrap();
+        assert_eq!(block.first(), Ipv4Addr::new(10, 0, 0, 0));
+        assert_eq!(block.last(), Ipv4Addr::new(10, 255, 255, 255));
+
+        let block = Cidr::<Ipv4Addr>::new(Ipv4Addr::new(10, 1, 2, 3), 32).unwrap();
+        assert_eq!(block.first(), Ipv4Addr::new(10, 1, 2, 3));
+        assert_eq!(block.last(), Ipv4Addr::new(10, 1, 2, 3));
+
+        let block = Cidr::<Ipv6Addr>::new("2001:db8::1".parse().unwrap(), 0).unwrap();
+        assert_eq!(block.first(), Ipv6Addr::UNSPECIFIED);
+        assert_eq!(block.last(), Ipv6Addr::from(u128::MAX));
+
+        let block = Cidr::<IpAddr>::new("192.168.1.1".parse().unwrap(), 16).unwrap();
+        assert_eq!(block.first(), "192.168.0.0".parse::<IpAddr>().unwrap());
+        assert_eq!(block.last(), "192.168.255.255".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn cidr_prefix_too_long() {
+        assert!(matches!(
+            Cidr::<Ipv4Addr>::new(Ipv4Addr::LOCALHOST, 33),
+            Err(OreError::InvalidCidr)
+        ));
+        assert!(matches!(
+            Cidr::<IpAddr>::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 64),
+            Err(OreError::InvalidCidr)
+        ));
+        assert!(Cidr::<IpAddr>::new(IpAddr::V6(Ipv6Addr::LOCALHOST), 64).is_ok());
+        assert!(Cidr::<Ipv6Addr>::new(Ipv6Addr::LOCALHOST, 129).is_err());
+    }
+
+    #[test]
+    fn ipv4_sorts_with_mapped_ipv6() {
+        let ore: Ore = init_ore();
+        let v4 = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))
+            .encrypt(&ore)
+            .unwrap();
+        let mapped = IpAddr::V6(Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped())
+            .encrypt(&ore)
+            .unwrap();
+        let v6 = IpAddr::V6("2001:db8::1".parse().unwrap())
+            .encrypt(&ore)
+            .unwrap();
+
+        assert!(v4 == mapped);
+        assert!(v4 < v6);
+        assert_eq!(
+            IpAddr::decrypt(&mapped, &ore).unwrap(),
+            IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))
+        );
+    }
+}
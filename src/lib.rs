@@ -18,10 +18,29 @@
 //! ## Example: Encrypt a number with ORE.
 //!
 //! To encrypt a number you need to initalize an [`OreCipher`] as well as `use` the [`OreEncrypt`] trait
-//! which comes with implementations for `u32` and `u64`.
+//! which comes with implementations for unsigned integers (`u8` to `u128`), `f32`, `f64`, signed integers (`i8` to `i128`)
+//! and pairs of those (such as `(u32, u64)`) which order by their first component and then their second.
+//! Structs can be encrypted as ordered composite keys with `#[derive(OreEncrypt)]` which is available
+//! with the `derive` feature (see the `ore-rs-derive` crate).
+//! Dates, timestamps and durations from the [chrono](https://crates.io/crates/chrono) and
+//! [time](https://crates.io/crates/time) crates are supported with the `chrono` and `time` features.
+//! Both crates share an encoding so values encrypted from either compare with each other.
+//! Exact decimal amounts can be encrypted as a [`FixedPoint`] or, with the `rust_decimal` feature,
+//! as a `Scaled<PRECISION, SCALE>` (or with a `DecimalEncoding`) for `rust_decimal::Decimal` values.
+//! IP addresses (`Ipv4Addr`, `Ipv6Addr` and `IpAddr`) are supported, too, and a [`Cidr`] block can
+//! be turned into a pair of encrypted bounds for range queries.
+//! Identifiers from the [uuid](https://crates.io/crates/uuid) and [ulid](https://crates.io/crates/ulid)
+//! crates are supported with the `uuid` feature. Time-ordered identifiers (UUIDv7 and ULIDs) keep
+//! their order when encrypted so they can be range scanned by creation time.
 //!
-//! To initalize the Cipher, you must decide on the scheme you want to use. There is only one ORE
-//! Scheme right now so that's easy but in the future more schemes will become available.
+//! To initalize the Cipher, you must decide on the scheme you want to use. The schemes are:
+//!
+//! * [`scheme::bit2`]: uses a 2-bit indicator function and is the most widely used scheme
+//! * [`scheme::bit3`]: uses a trinary (3-valued) indicator function so that [`Left`] ciphertexts
+//!   can be compared directly with [`Right`] ciphertexts (stored values don't need a left ciphertext)
+//! * [`scheme::bit4`]: like `bit2` but with 4-bit blocks for much smaller right ciphertexts
+//! * [`scheme::bit16`]: like `bit2` but with 16-bit blocks which leaks less about prefixes but has
+//!   much larger right ciphertexts and slower encryption
 //!
 //! An `OreCipher` also requires 2 keys (16-bytes each) and an 8-byte seed.
 //!
@@ -44,6 +63,19 @@
 //!
 //! *Note that a cipher must be mutable as it manages internal state*.
 //!
+//! Right ciphertexts include a random nonce so encrypting the same value twice produces different
+//! bytes. For reproducible output (e.g. test vectors), a scheme can be initialized with a fixed
+//! RNG seed instead:
+//!
+//! ```rust
+//! # use ore_rs::{OreEncrypt, OreOutput, scheme::bit2::OreAes128ChaCha20};
+//! # use hex_literal::hex;
+//! # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! let ore = OreAes128ChaCha20::init_with_seed(&k1, &k2, [0; 32]).unwrap();
+//! let a = 456u64.encrypt(&ore).unwrap();
+//! ```
+//!
 //!
 //! ## Example: Comparing 2 CipherTexts
 //!
@@ -89,10 +121,114 @@
 //! let result = a > b; // compilation error
 //! ```
 //!
+//! ## Example: Comparing a Left CipherText
+//!
+//! A [`Left`] ciphertext is much smaller than a full [`CipherText`] and contains no random nonce.
+//! It can't be compared to another `Left` but can be compared to a full `CipherText` which makes
+//! it a good fit for query terms.
+//!
+//! ```rust
+//! # use ore_rs::{
+//! #     CipherText,
+//! #     OreCipher,  // Main ORE Cipher trait
+//! #     OreEncrypt, // Traits for encrypting primitive types (e.g. u64)
+//! #     OreOutput,
+//! #     scheme::bit2::OreAes128ChaCha20 // Specific scheme we want to use
+//! # };
+//! # use hex_literal::hex;
+//! # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+//! let stored = 1024u64.encrypt(&ore).unwrap();
+//! let query = 456u64.encrypt_left(&ore).unwrap();
+//!
+//! assert!(query < stored);
+//!
+//! // Or directly on serialized values
+//! let result = OreAes128ChaCha20::compare_left_raw_slices(&query.to_bytes(), &stored.to_bytes());
+//! assert_eq!(result, Some(std::cmp::Ordering::Less));
+//! ```
+//!
+//! ## Example: Decrypting
+//!
+//! Anyone holding both keys can recover the plaintext from a [`Left`] or a full [`CipherText`].
+//!
+//! ```rust
+//! # use ore_rs::{
+//! #     OreCipher,  // Main ORE Cipher trait
+//! #     OreEncrypt, // Traits for encrypting primitive types (e.g. u64)
+//! #     scheme::bit2::OreAes128ChaCha20 // Specific scheme we want to use
+//! # };
+//! # use hex_literal::hex;
+//! # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+//! let a = 456u64.encrypt(&ore).unwrap();
+//!
+//! assert_eq!(u64::decrypt(&a, &ore).unwrap(), 456);
+//! assert_eq!(ore.decrypt(&a).unwrap(), 456u64.to_be_bytes());
+//! ```
+//!
+//! ## Example: Encrypting floats
+//!
+//! Floats are encrypted by their bit pattern so `-0.0` and `0.0` produce different ciphertexts and
+//! NaNs sort at either end depending on their sign. Encrypt them with a [`FloatPolicy`] to reject
+//! those values or to get the same total order as SQL's `ORDER BY`.
+//!
+//! ```rust
+//! # use ore_rs::{
+//! #     FloatPolicy,
+//! #     OreCipher,       // Main ORE Cipher trait
+//! #     OreEncrypt,      // Traits for encrypting primitive types (e.g. u64)
+//! #     OreEncryptFloat, // Encrypting floats under a FloatPolicy
+//! #     scheme::bit2::OreAes128ChaCha20 // Specific scheme we want to use
+//! # };
+//! # use hex_literal::hex;
+//! # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+//! let policy = FloatPolicy::Canonicalize;
+//!
+//! let zero = (-0.0f64).encrypt_with_policy(&ore, policy).unwrap();
+//! let nan = f64::NAN.encrypt_with_policy(&ore, policy).unwrap();
+//! let inf = f64::INFINITY.encrypt_with_policy(&ore, policy).unwrap();
+//!
+//! assert!(zero == 0.0f64.encrypt(&ore).unwrap());
+//! assert!(nan > inf);
+//!
+//! assert!(f64::NAN.encrypt_with_policy(&ore, FloatPolicy::Reject).is_err());
+//! ```
+//!
+//! ## Example: Encrypting strings
+//!
+//! Plaintexts whose length is only known at runtime (such as strings) are encrypted into a
+//! [`VarCipherText`] which orders lexicographically. Note that the length of the plaintext isn't
+//! hidden but it can be capped with a maximum length.
+//!
+//! ```rust
+//! # use ore_rs::{
+//! #     OreCipher,  // Main ORE Cipher trait
+//! #     scheme::bit2::OreAes128ChaCha20 // Specific scheme we want to use
+//! # };
+//! # use hex_literal::hex;
+//! # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+//! # let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+//! let a = ore.encrypt_var("apple".as_bytes(), None).unwrap();
+//! let b = ore.encrypt_var("apples".as_bytes(), None).unwrap();
+//! let c = ore.encrypt_var("banana".as_bytes(), Some(3)).unwrap();
+//!
+//! assert!(a < b);
+//! assert!(b < c);
+//! assert_eq!(ore.decrypt_var(&c).unwrap(), b"ban");
+//! ```
+//!
 //! ## Serializing/Deserializing
 //!
-//! *Note: this library doesn't use [Serde](https://crates.io/crates/serde) due to some complexities
-//! with GenericArray used in the [AES](https://crates.io/crates/aes) library. This may change in the future.*
+//! With the `serde` feature, [`CipherText<S, N>`], [`Left<S, N>`] and [`Right<S, N>`] implement
+//! [Serde](https://crates.io/crates/serde)'s `Serialize` and `Deserialize`. Binary formats
+//! (such as bincode) store the ciphertext as bytes and human-readable formats (such as JSON)
+//! store it as a hex string.
 //!
 //! To serialize a [`CipherText<S, N>`] to a vector of bytes:
 //!
@@ -133,6 +269,14 @@
 //! let ct = CipherText::<OreAes128ChaCha20, 8>::from_bytes(&bytes).unwrap();
 //! # assert!(ct == a);
 //! ```
+//!
+//! The bytes from `to_bytes` don't record which scheme or number of blocks produced them.
+//! [`OreEnvelope::to_envelope`] prefixes them with a header containing a magic number, a format
+//! version, the scheme id and the number of blocks so that [`OreEnvelope::from_envelope`] (and
+//! [`OreEnvelopeScheme::compare_envelopes`]) can reject mismatched data.
+//!
+//! To compare stored ciphertexts without copying them, [`CipherTextRef`] and [`LeftRef`] wrap
+//! a validated slice of bytes and implement the same ordering as the owned types.
 
 mod ciphertext;
 mod convert;
@@ -140,11 +284,25 @@ mod encrypt;
 mod primitives;
 pub mod scheme;
 pub use crate::ciphertext::*;
-pub use crate::encrypt::OreEncrypt;
+pub use crate::convert::FloatPolicy;
+pub use crate::encrypt::collation;
+pub use crate::encrypt::{
+    Cidr, Descending, FixedPoint, NullsLast, OreEncrypt, OreEncryptFloat, OrePlaintext,
+};
+#[cfg(feature = "rust_decimal")]
+pub use crate::encrypt::{DecimalEncoding, Scaled};
+#[cfg(feature = "derive")]
+pub use ore_rs_derive::OreEncrypt;
 use primitives::PrpError;
 use std::cmp::Ordering;
 use thiserror::Error;
 
+/* Used by the code generated by ore-rs-derive. Not part of the public API. */
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::encrypt::{encode_with, read_desc, write_desc, OrderedBytes};
+}
+
 pub type PlainText<const N: usize> = [u8; N];
 
 #[derive(Debug, Error)]
@@ -155,6 +313,16 @@ pub enum OreError {
     PrpError(#[from] PrpError),
     #[error("Randomness Error")]
     RandError(#[from] rand::Error),
+    #[error("Failed to decrypt ciphertext")]
+    DecryptFailed,
+    #[error("Float value is not allowed by the float policy")]
+    InvalidFloat,
+    #[error("Decimal value doesn't fit the decimal encoding")]
+    InvalidDecimal,
+    #[error("CIDR prefix length is longer than the address")]
+    InvalidCidr,
+    #[error("Sort key contains a zero byte")]
+    InvalidSortKey,
 }
 
 pub trait OreCipher: Sized {
@@ -171,9 +339,79 @@ pub trait OreCipher: Sized {
         input: &PlainText<N>,
     ) -> Result<CipherText<Self, N>, OreError>;
 
+    /// Recover the plaintext from a [`Left`] ciphertext.
+    ///
+    /// Returns [`OreError::DecryptFailed`] if the ciphertext wasn't produced with this cipher's keys.
+    /// Schemes which don't support decryption always return [`OreError::DecryptFailed`].
+    fn decrypt_left<const N: usize>(
+        &self,
+        _input: &Left<Self, N>,
+    ) -> Result<PlainText<N>, OreError> {
+        Err(OreError::DecryptFailed)
+    }
+
+    /// Recover the plaintext from a [`CipherText`] (only the left half is required).
+    fn decrypt<const N: usize>(
+        &self,
+        input: &CipherText<Self, N>,
+    ) -> Result<PlainText<N>, OreError> {
+        self.decrypt_left(&input.left)
+    }
+
     fn compare_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering>;
+
+    /// Compare a serialized [`Left`] ciphertext (`a`) against a serialized
+    /// [`CipherText`] (`b`) of the same number of blocks.
+    ///
+    /// Returns `None` if the slices don't have matching block counts. Schemes which don't
+    /// implement this comparison always return `None`.
+    fn compare_left_raw_slices(_a: &[u8], _b: &[u8]) -> Option<Ordering> {
+        None
+    }
+}
+
+/// Comparison of the ciphertexts produced by a scheme.
+///
+/// [`CipherText`] (and [`Left`] against [`CipherText`]) comparisons are implemented for every scheme
+/// that implements this trait, regardless of the RNG it uses.
+pub trait OreCompare: OreCipher {
+    /// Compare a [`Left`] ciphertext against a full [`CipherText`] of the same number of blocks.
+    fn compare_left<const N: usize>(a: &Left<Self, N>, b: &CipherText<Self, N>) -> Ordering;
+}
+
+/// Schemes which can encrypt plaintexts whose length is only known at runtime.
+///
+/// [`VarLeft`], [`VarRight`] and [`VarCipherText`] can only be used with schemes which implement
+/// this trait (currently only [`scheme::bit2`]).
+pub trait VarOreCipher: OreCipher {
+    /// Compare a [`VarLeft`] ciphertext against a full [`VarCipherText`] of any length.
+    fn compare_left_var(a: &VarLeft<Self>, b: &VarCipherText<Self>) -> Ordering;
+
+    /// Compare two serialized [`VarCipherText`]s.
+    ///
+    /// Returns `None` if either slice isn't a valid ciphertext.
+    fn compare_var_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering>;
+
+    /// Compare a serialized [`VarLeft`] ciphertext (`a`) against a serialized [`VarCipherText`] (`b`).
+    ///
+    /// Returns `None` if either slice isn't a valid ciphertext.
+    fn compare_left_var_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering>;
 }
 
 #[cfg(test)]
 #[macro_use]
 extern crate quickcheck;
+
+#[cfg(test)]
+mod test_utils {
+    use crate::OreCipher;
+    use hex_literal::hex;
+
+    /* A cipher of any scheme with fixed test keys */
+    pub(crate) fn init_ore<S: OreCipher>() -> S {
+        let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+        let k2: [u8; 16] = hex!("d0d007a5 3f9a6848 83bc1f21 0f6595a3");
+
+        OreCipher::init(&k1, &k2).unwrap()
+    }
+}
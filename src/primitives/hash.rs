@@ -45,6 +45,48 @@ impl Hash for Aes128Z2Hash {
     }
 }
 
+/*
+ * Hash with output in Z3 (i.e. 0, 1 or 2) used by the trinary indicator scheme.
+ */
+#[derive(ZeroizeOnDrop)]
+pub struct Aes128Z3Hash {
+    cipher: Aes128,
+}
+
+/*
+ * Reduces the first 8 bytes of an AES output mod 3.
+ * The bias this introduces is less than 2^-62.
+ */
+#[inline]
+fn reduce_z3(block: &[u8]) -> u8 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&block[0..8]);
+    (u64::from_le_bytes(bytes) % 3) as u8
+}
+
+impl Hash for Aes128Z3Hash {
+    fn new(key: &HashKey) -> Self {
+        let key_array = GenericArray::from_slice(key);
+        let cipher = Aes128::new(key_array);
+        Self { cipher }
+    }
+
+    fn hash(&self, data: &[u8]) -> u8 {
+        assert_eq!(data.len(), 16);
+        let mut output = [0u8; 16];
+        output.clone_from_slice(data);
+        let block = GenericArray::from_mut_slice(&mut output);
+        self.cipher.encrypt_block(block);
+        reduce_z3(&output)
+    }
+
+    fn hash_all(&self, data: &mut [AesBlock]) -> Vec<u8> {
+        self.cipher.encrypt_blocks(data);
+
+        data.iter().map(|block| reduce_z3(block)).collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -56,6 +98,12 @@ mod tests {
         Hash::new(key_array)
     }
 
+    fn init_z3_hash() -> Aes128Z3Hash {
+        let key: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+        let key_array = GenericArray::from_slice(&key);
+        Hash::new(key_array)
+    }
+
     #[test]
     fn hash_test_1() {
         let hash = init_hash();
@@ -89,4 +137,17 @@ mod tests {
 
         hash.hash(&input);
     }
+
+    #[test]
+    fn z3_hash_matches_hash_all() {
+        let hash = init_z3_hash();
+        let mut inputs: [AesBlock; 64] = [Default::default(); 64];
+        for (i, input) in inputs.iter_mut().enumerate() {
+            input[0] = i as u8;
+        }
+
+        let expected: Vec<u8> = inputs.iter().map(|input| hash.hash(input)).collect();
+        assert!(expected.iter().all(|&h| h < 3));
+        assert_eq!(expected, hash.hash_all(&mut inputs));
+    }
 }
@@ -24,6 +24,121 @@ impl Prf for Aes128Prf {
     }
 }
 
+/*
+ * Derives the key for a PrefixPrf from a PRF as F(k, label).
+ *
+ * The CBC-MAC in PrefixPrf is only a secure PRF if nothing else uses its key. The single block
+ * layouts of the bit2 and bit3 schemes evaluate F under k directly on blocks whose last byte is
+ * 0 or a block number below 15, so labels end in 0xff. The derived keys are then never the
+ * output of any other evaluation of F under k and never appear in a ciphertext.
+ */
+pub fn derive_prefix_prf<P: Prf>(prf: &P, label: &[u8; 15]) -> P {
+    let mut key: [AesBlock; 1] = Default::default();
+    key[0][0..15].clone_from_slice(label);
+    key[0][15] = PREFIX_KEY_LABEL_END;
+    prf.encrypt_all(&mut key);
+
+    P::new(&key[0])
+}
+
+pub const PREFIX_KEY_LABEL_END: u8 = 0xff;
+
+/*
+ * Evaluates a PRF over every prefix of a plaintext, i.e. F(k, x|n || v) for each n, without
+ * needing the whole prefix to fit into a single AES block.
+ *
+ * This is a CBC-MAC over one block per plaintext symbol. Each block is tagged with a flag (init,
+ * absorb or final) and the position of the symbol so the set of inputs is prefix-free which is
+ * what makes CBC-MAC a secure PRF for variable length inputs. Only the outputs of final blocks
+ * are ever revealed. The PRF must be keyed with derive_prefix_prf.
+ */
+pub struct PrefixPrf<'p, P: Prf> {
+    prf: &'p P,
+    state: AesBlock,
+    position: u32,
+}
+
+const PREFIX_FLAG_INIT: u8 = 0;
+const PREFIX_FLAG_ABSORB: u8 = 1;
+const PREFIX_FLAG_FINAL: u8 = 2;
+
+/* The flag and the position take up the first 5 bytes of each block */
+pub const PREFIX_MAX_SYMBOL_SIZE: usize = 11;
+
+impl<'p, P: Prf> PrefixPrf<'p, P> {
+    /*
+     * The domain separates the outputs of different schemes and plaintext lengths
+     * under the same key and can be up to 15 bytes.
+     */
+    pub fn new(prf: &'p P, domain: &[u8]) -> Self {
+        debug_assert!(domain.len() < 16);
+        let mut state: [AesBlock; 1] = Default::default();
+        state[0][0] = PREFIX_FLAG_INIT;
+        state[0][1..=domain.len()].clone_from_slice(domain);
+        prf.encrypt_all(&mut state);
+
+        Self {
+            prf,
+            state: state[0],
+            position: 0,
+        }
+    }
+
+    /* Append a plaintext symbol to the prefix */
+    pub fn absorb(&mut self, symbol: &[u8]) {
+        let mut block = [self.encode(PREFIX_FLAG_ABSORB, symbol)];
+        self.prf.encrypt_all(&mut block);
+        self.state = block[0];
+        self.position += 1;
+    }
+
+    /*
+     * Compute F(k, prefix || value).
+     * Values are zero padded so callers should always use values of the same length.
+     */
+    pub fn evaluate(&self, value: &[u8]) -> AesBlock {
+        let mut block = [self.encode(PREFIX_FLAG_FINAL, value)];
+        self.prf.encrypt_all(&mut block);
+        block[0]
+    }
+
+    /*
+     * Compute F(k, prefix || value(j)) for every block in output in one batch.
+     * The value function writes the value for block j into the given slice.
+     */
+    pub fn evaluate_all<F>(&self, output: &mut [AesBlock], value: F)
+    where
+        F: Fn(usize, &mut [u8]),
+    {
+        for (j, block) in output.iter_mut().enumerate() {
+            *block = self.encode(PREFIX_FLAG_FINAL, &[]);
+            let mut v = [0u8; PREFIX_MAX_SYMBOL_SIZE];
+            value(j, &mut v);
+            block[5..]
+                .iter_mut()
+                .zip(v.iter())
+                .for_each(|(b, v)| *b ^= v);
+        }
+        self.prf.encrypt_all(output);
+    }
+
+    #[inline]
+    fn encode(&self, flag: u8, symbol: &[u8]) -> AesBlock {
+        debug_assert!(symbol.len() <= PREFIX_MAX_SYMBOL_SIZE);
+        let mut block = self.state;
+        block[0] ^= flag;
+        block[1..5]
+            .iter_mut()
+            .zip(self.position.to_be_bytes().iter())
+            .for_each(|(b, p)| *b ^= p);
+        block[5..(5 + symbol.len())]
+            .iter_mut()
+            .zip(symbol.iter())
+            .for_each(|(b, s)| *b ^= s);
+        block
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,4 +180,71 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn prefix_prf_evaluate_all_matches_evaluate() {
+        let prf = init_prf();
+        let mut prefix = PrefixPrf::new(&prf, b"test");
+        prefix.absorb(&[1]);
+        prefix.absorb(&[2]);
+
+        let mut output: [AesBlock; 4] = Default::default();
+        prefix.evaluate_all(&mut output, |j, value| value[0] = j as u8);
+
+        for (j, block) in output.iter().enumerate() {
+            assert_eq!(*block, prefix.evaluate(&[j as u8]));
+        }
+    }
+
+    #[test]
+    fn prefix_prf_depends_on_prefix_and_domain() {
+        let prf = init_prf();
+        let mut a = PrefixPrf::new(&prf, b"test");
+        let mut b = PrefixPrf::new(&prf, b"test");
+        let c = PrefixPrf::new(&prf, b"other");
+
+        assert_eq!(a.evaluate(&[7]), b.evaluate(&[7]));
+        assert_ne!(a.evaluate(&[7]), c.evaluate(&[7]));
+
+        a.absorb(&[1]);
+        b.absorb(&[2]);
+        assert_ne!(a.evaluate(&[7]), b.evaluate(&[7]));
+    }
+
+    /*
+     * The PrefixPrf init block for a 16 byte bit2 plaintext is the same as the single block
+     * prefix input for the first 9 bytes of a 10 to 15 byte plaintext. Under a shared key the
+     * CBC-MAC state would equal a PRP key of the single block layout.
+     */
+    #[test]
+    fn derived_prefix_prf_is_separate_from_single_block_inputs() {
+        let prf = init_prf();
+        let domain = *b"bit2\0\0\0\x10";
+        let mut legacy: [AesBlock; 1] = Default::default();
+        legacy[0][1..9].clone_from_slice(&domain);
+        prf.encrypt_all(&mut legacy);
+
+        assert_eq!(PrefixPrf::new(&prf, &domain).state, legacy[0]);
+
+        let derived = derive_prefix_prf(&prf, b"ore-prefix-prp\0");
+        assert_ne!(PrefixPrf::new(&derived, &domain).state, legacy[0]);
+    }
+
+    /* No single block input (bit2 and bit3 with N up to 15) can be a key derivation label */
+    #[test]
+    fn single_block_inputs_never_end_with_label_byte() {
+        for n_blocks in 1..=15 {
+            for n in 0..n_blocks {
+                /* Prefix blocks (k2) and left blocks / RO inputs (k1) with all-0xff bytes */
+                let mut prefix = [0u8; 16];
+                prefix[0..n].iter_mut().for_each(|b| *b = 0xff);
+                let mut left = prefix;
+                left[n] = 0xff;
+                left[n_blocks] = n as u8;
+
+                assert_ne!(prefix[15], PREFIX_KEY_LABEL_END);
+                assert_ne!(left[15], PREFIX_KEY_LABEL_END);
+            }
+        }
+    }
 }
@@ -19,26 +19,32 @@ impl<T: Zeroize, const N: usize> Drop for KnuthShufflePRP<T, N> {
 // Impl the ZeroizeOnDrop marker trait since we're zeroizing above
 impl<T: Zeroize, const N: usize> ZeroizeOnDrop for KnuthShufflePRP<T, N> {}
 
-impl Prp<u8> for KnuthShufflePRP<u8, 256> {
+impl<const N: usize> Prp<u8> for KnuthShufflePRP<u8, N> {
     /*
-     * Initialize an 8-bit (256 element) PRP using a KnuthShuffle
-     * and a 64-bit random seed
+     * Initialize an N element PRP (for N up to 256, e.g. 8-bit or 4-bit blocks)
+     * using a KnuthShuffle and a 64-bit random seed
      */
     fn new(key: &[u8]) -> PrpResult<Self> {
+        debug_assert!(N <= 256);
         let mut rng = Aes128Prng::init(key); // TODO: Use Result type here, too
 
         let mut perm = Self {
-            permutation: [0u8; 256],
-            inverse: [0u8; 256],
+            permutation: [0u8; N],
+            inverse: [0u8; N],
         };
 
         // Initialize values
-        for i in 0..=255 {
+        for i in 0..N {
             perm.permutation[i] = i as u8;
         }
 
-        (0..=255usize).rev().for_each(|i| {
-            let j = rng.gen_range(i as u8);
+        /* The last step (i = 0) would always swap 0 with itself so it's skipped */
+        (1..N).rev().for_each(|i| {
+            let j = if N == 256 {
+                rng.gen_range_unmasked(i as u8)
+            } else {
+                rng.gen_range(i as u8)
+            };
             perm.permutation.swap(i, j as usize);
         });
 
@@ -77,6 +83,61 @@ impl Prp<u8> for KnuthShufflePRP<u8, 256> {
     }
 }
 
+/*
+ * A PRP over 16-bit values.
+ *
+ * The permutation tables are 128KiB each so they're kept on the heap.
+ */
+#[derive(Zeroize)]
+pub struct KnuthShufflePRP16 {
+    permutation: Vec<u16>,
+    inverse: Vec<u16>,
+}
+
+impl Drop for KnuthShufflePRP16 {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for KnuthShufflePRP16 {}
+
+impl Prp<u16> for KnuthShufflePRP16 {
+    fn new(key: &[u8]) -> PrpResult<Self> {
+        let mut rng = Aes128Prng::init(key);
+
+        let mut perm = Self {
+            permutation: (0..=u16::MAX).collect(),
+            inverse: vec![0u16; 65536],
+        };
+
+        (1..65536usize).rev().for_each(|i| {
+            let j = rng.gen_range_u16(i as u16);
+            perm.permutation.swap(i, j as usize);
+        });
+
+        for (index, val) in perm.permutation.iter().enumerate() {
+            perm.inverse[*val as usize] = index as u16;
+        }
+
+        Ok(perm)
+    }
+
+    fn permute(&self, input: u16) -> PrpResult<u16> {
+        match self.inverse.get(usize::from(input)) {
+            Some(i) => Ok(*i),
+            None => Err(PrpError),
+        }
+    }
+
+    fn invert(&self, input: u16) -> PrpResult<u16> {
+        match self.permutation.get(usize::from(input)) {
+            Some(i) => Ok(*i),
+            None => Err(PrpError),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,4 +162,69 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_invert_4bit() -> Result<(), PrpError> {
+        let key: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0eaa");
+        let prp: KnuthShufflePRP<u8, 16> = Prp::new(&key)?;
+
+        for i in 0..16 {
+            assert_eq!(i, prp.invert(prp.permute(i)?)?);
+        }
+        assert!(prp.permute(16).is_err());
+
+        Ok(())
+    }
+
+    /* 256 element permutations must not change as existing bit2 and bit3 ciphertexts use them */
+    #[test]
+    fn test_known_permutation() -> Result<(), PrpError> {
+        let prp = init_prp()?;
+        let expected = hex!(
+            "3e70d1ca766ed39b58f4aa24f5b90f050969d5b3c841e591aeb5fa17deeefe3a"
+            "d85dec147e4679c3481f5b7472cfddd206956018b013bead0d2d15df59ebf0c5"
+            "781ed97d574a291620b440b1e7b792fd076c22a6006b9c35d466646d85842677"
+            "9638324c340449901de21b19e0e1a1b6f12e1054d7d6370227a20e0a98f263a0"
+            "7b11c7258ce4ab5a8bc247effc9f813b617ac6526a0b9a73e8a423a587b8554d"
+            "450ccc886fbbf89975035ec47fbdfb3d68c04312393fe3a98230bfcd94da6567"
+            "56ac51cb44c1ce8efff3e9e62c8fdcafbca7b27c1a333608c9edf94b864f802a"
+            "50714e935cea8a5fd09ea3211c42baf7312f62978983db012b9da83cf628538d"
+        );
+
+        for i in 0..=255 {
+            assert_eq!(prp.permute(i)?, expected[i as usize]);
+        }
+
+        Ok(())
+    }
+
+    /* The 4-bit PRP (bit4) must be a bijection on 0..16 for any key */
+    #[test]
+    fn test_4bit_bijection() -> Result<(), PrpError> {
+        for k in 0..=255u8 {
+            let prp: KnuthShufflePRP<u8, 16> = Prp::new(&[k; 16])?;
+            let mut seen = [false; 16];
+
+            for i in 0..16 {
+                let p = prp.permute(i)?;
+                assert!(p < 16 && !seen[p as usize]);
+                seen[p as usize] = true;
+                assert_eq!(i, prp.invert(p)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invert_16bit() -> Result<(), PrpError> {
+        let key: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0eaa");
+        let prp: KnuthShufflePRP16 = Prp::new(&key)?;
+
+        for i in 0..=u16::MAX {
+            assert_eq!(i, prp.invert(prp.permute(i)?)?);
+        }
+
+        Ok(())
+    }
 }
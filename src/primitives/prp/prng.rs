@@ -45,8 +45,28 @@ impl Aes128Prng {
         value
     }
 
-    /* Find a uniform random number up to and including max */
+    /*
+     * Find a uniform random number up to and including max.
+     * Candidates are masked to the smallest covering power of 2 so that no more than 2 tries
+     * are needed on average.
+     */
     pub fn gen_range(&mut self, max: u8) -> u8 {
+        let mask = u8::MAX.checked_shr(max.leading_zeros()).unwrap_or(0);
+        loop {
+            let candidate = self.next_byte() & mask;
+
+            if candidate <= max {
+                return candidate;
+            }
+        }
+    }
+
+    /*
+     * Find a uniform random number up to and including max by rejecting full bytes.
+     * This is slow for small values of max but is kept so that 256 element permutations (used by
+     * the bit2 and bit3 schemes) stay the same as in earlier releases.
+     */
+    pub fn gen_range_unmasked(&mut self, max: u8) -> u8 {
         loop {
             let candidate = self.next_byte();
 
@@ -57,6 +77,22 @@ impl Aes128Prng {
         }
     }
 
+    /*
+     * Find a uniform random 16-bit number up to and including max.
+     * Candidates are masked to the smallest covering power of 2 so that no more than 2 tries
+     * are needed on average.
+     */
+    pub fn gen_range_u16(&mut self, max: u16) -> u16 {
+        let mask = u16::MAX.checked_shr(max.leading_zeros()).unwrap_or(0);
+        loop {
+            let candidate = u16::from_be_bytes([self.next_byte(), self.next_byte()]) & mask;
+
+            if candidate <= max {
+                return candidate;
+            }
+        }
+    }
+
     fn generate(&mut self) {
         self.ptr = (0, 0);
         for i in 0..16 {
@@ -113,4 +149,26 @@ mod tests {
             prg.next_byte();
         }
     }
+
+    #[test]
+    fn prg_gen_range() {
+        let mut prg = init_prng();
+
+        for max in [0, 1, 2, 15, 16, 127, 128, 255] {
+            for _i in 0..100 {
+                assert!(prg.gen_range(max) <= max);
+            }
+        }
+    }
+
+    #[test]
+    fn prg_gen_range_u16() {
+        let mut prg = init_prng();
+
+        for max in [0, 1, 2, 255, 256, 1000, u16::MAX] {
+            for _i in 0..100 {
+                assert!(prg.gen_range_u16(max) <= max);
+            }
+        }
+    }
 }
@@ -1 +1,5 @@
+pub mod bit16;
 pub mod bit2;
+pub mod bit3;
+pub mod bit4;
+mod prefixed;
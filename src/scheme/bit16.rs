@@ -0,0 +1,452 @@
+/*
+ * Block ORE Implemenation using 16-bit plaintext blocks and a 2-bit indicator function
+ *
+ * Each block covers 2 plaintext bytes so there are half as many blocks as with 8-bit blocks
+ * (i.e. less prefix leakage) but right ciphertexts are 256 times larger (8KiB per block) and
+ * encryption is much slower. N is still the number of plaintext bytes and must be even.
+ *
+ * The 16-byte PRF output of each block is split over the left blocks of its 2 bytes and the
+ * right ciphertext for each block is split over the right blocks of its 2 bytes.
+ */
+
+use crate::{
+    ciphertext::*,
+    primitives::{
+        hash::Aes128Z2Hash,
+        prf::{Aes128Prf, PrefixPrf},
+        prp::KnuthShufflePRP16,
+        AesBlock, Hash, HashKey, Prf, Prp, NONCE_SIZE,
+    },
+    scheme::prefixed::PrefixKeys,
+    OreCipher, OreCompare, OreError, PlainText,
+};
+
+use aes::cipher::generic_array::GenericArray;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use subtle_ng::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::ZeroizeOnDrop;
+
+pub mod block_types;
+pub use self::block_types::*;
+
+/// Block ORE with 16-bit plaintext blocks.
+///
+/// Every block covers 2 bytes of the plaintext so only plaintexts with an even number of bytes
+/// (i.e. an even `N`) can be encrypted:
+///
+/// ```rust
+/// # use ore_rs::{OreCipher, OreEncrypt, scheme::bit16::OreAes128ChaCha20};
+/// # use hex_literal::hex;
+/// # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+/// assert!(1u16.encrypt(&ore).unwrap() < 2u16.encrypt(&ore).unwrap());
+/// ```
+///
+/// Encrypting an odd number of bytes, such as a `u8`, fails to compile:
+///
+/// ```compile_fail
+/// # use ore_rs::{OreCipher, OreEncrypt, scheme::bit16::OreAes128ChaCha20};
+/// # use hex_literal::hex;
+/// # let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// # let k2: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+/// let ore: OreAes128ChaCha20 = OreCipher::init(&k1, &k2).unwrap();
+/// let _ = 1u8.encrypt(&ore);
+/// ```
+#[derive(Debug, ZeroizeOnDrop)]
+pub struct OreAes128<R: Rng + SeedableRng> {
+    prefix: PrefixKeys,
+    #[zeroize(skip)]
+    rng: RefCell<R>,
+}
+
+pub type OreAes128ChaCha20 = OreAes128<ChaCha20Rng>;
+
+/* Define some convenience types */
+type EncryptLeftResult<R, const N: usize> = Result<Left<OreAes128<R>, N>, OreError>;
+type EncryptResult<R, const N: usize> = Result<CipherText<OreAes128<R>, N>, OreError>;
+type DecryptResult<const N: usize> = Result<PlainText<N>, OreError>;
+
+/* Number of values in a 16-bit block */
+const BLOCK_DOMAIN: usize = 65536;
+
+/* Fails to compile (when used) for plaintexts with an odd number of bytes */
+struct AssertEven<const N: usize>;
+
+impl<const N: usize> AssertEven<N> {
+    const OK: () = assert!(
+        N & 1 == 0,
+        "16-bit blocks need an even number of plaintext bytes"
+    );
+}
+
+fn cmp(a: u16, b: u16) -> u8 {
+    u8::from(a > b)
+}
+
+/* Separates the PRF outputs from other schemes and plaintext lengths */
+fn domain<const N: usize>() -> [u8; 9] {
+    let mut domain = *b"bit16\0\0\0\0";
+    domain[5..].copy_from_slice(&(N as u32).to_be_bytes());
+    domain
+}
+
+#[inline]
+fn block_value(input: &[u8], n: usize) -> u16 {
+    u16::from_be_bytes([input[2 * n], input[2 * n + 1]])
+}
+
+/* Joins the 2 halves of a PRF output */
+#[inline]
+fn join_halves(a: &LeftBlock8, b: &LeftBlock8) -> AesBlock {
+    let mut block = AesBlock::default();
+    block[0..8].copy_from_slice(a.as_slice());
+    block[8..16].copy_from_slice(b.as_slice());
+    block
+}
+
+impl<R: Rng + SeedableRng> OreAes128<R> {
+    /// Initialize a cipher which uses the given RNG to generate nonces for right ciphertexts.
+    ///
+    /// Ciphers initialized with the same keys and identical RNGs produce byte-identical ciphertexts
+    /// which is useful for test vectors. Use [`OreCipher::init`] everywhere else.
+    pub fn init_with_rng(k1: &[u8; 16], k2: &[u8; 16], rng: R) -> Result<Self, OreError> {
+        let prf1: Aes128Prf = Prf::new(GenericArray::from_slice(k1));
+        let prf2: Aes128Prf = Prf::new(GenericArray::from_slice(k2));
+
+        Ok(OreAes128 {
+            prefix: PrefixKeys::new(&prf1, &prf2),
+            rng: RefCell::new(rng),
+        })
+    }
+
+    /// Initialize a cipher with an RNG created from the given seed (see [`Self::init_with_rng`]).
+    pub fn init_with_seed(k1: &[u8; 16], k2: &[u8; 16], seed: R::Seed) -> Result<Self, OreError> {
+        Self::init_with_rng(k1, k2, R::from_seed(seed))
+    }
+}
+
+impl<R: Rng + SeedableRng> OreCipher for OreAes128<R> {
+    type LeftBlockType = LeftBlock8;
+    type RightBlockType = RightBlock4096;
+
+    fn init(k1: &[u8; 16], k2: &[u8; 16]) -> Result<Self, OreError> {
+        Self::init_with_rng(k1, k2, SeedableRng::from_entropy())
+    }
+
+    fn encrypt_left<const N: usize>(&self, x: &PlainText<N>) -> EncryptLeftResult<R, N> {
+        #[allow(clippy::let_unit_value)]
+        let () = AssertEven::<N>::OK;
+
+        let mut output = Left::<Self, N>::init();
+        let mut prp_prefix = PrefixPrf::new(&self.prefix.prp, &domain::<N>());
+        let mut ro_prefix = PrefixPrf::new(&self.prefix.ro, &domain::<N>());
+
+        for n in 0..(N / 2) {
+            let xn = block_value(x, n);
+
+            // Create PRP for the block from the prefix
+            let prp: KnuthShufflePRP16 = Prp::new(&prp_prefix.evaluate(&[]))?;
+            let xt = prp.permute(xn)?.to_be_bytes();
+
+            let f = ro_prefix.evaluate(&xt);
+            output.xt[2 * n..2 * n + 2].copy_from_slice(&xt);
+            output.f[2 * n].copy_from_slice(&f[0..8]);
+            output.f[2 * n + 1].copy_from_slice(&f[8..16]);
+
+            prp_prefix.absorb(&xn.to_be_bytes());
+            ro_prefix.absorb(&xn.to_be_bytes());
+        }
+
+        Ok(output)
+    }
+
+    fn encrypt<const N: usize>(&self, x: &PlainText<N>) -> EncryptResult<R, N> {
+        #[allow(clippy::let_unit_value)]
+        let () = AssertEven::<N>::OK;
+
+        let mut left = Left::<Self, N>::init();
+        let mut right = Right::<Self, N>::init();
+
+        // Generate a 16-byte random nonce
+        self.rng.borrow_mut().try_fill(&mut right.nonce)?;
+
+        let mut prp_prefix = PrefixPrf::new(&self.prefix.prp, &domain::<N>());
+        let mut ro_prefix = PrefixPrf::new(&self.prefix.ro, &domain::<N>());
+        let hasher: Aes128Z2Hash = Hash::new(AesBlock::from_slice(&right.nonce));
+
+        // Too big for the stack
+        let mut ro_keys: Vec<AesBlock> = vec![Default::default(); BLOCK_DOMAIN];
+
+        for n in 0..(N / 2) {
+            let xn = block_value(x, n);
+
+            // Create PRP for the block from the prefix
+            let prp: KnuthShufflePRP16 = Prp::new(&prp_prefix.evaluate(&[]))?;
+            let xt = prp.permute(xn)?.to_be_bytes();
+
+            let f = ro_prefix.evaluate(&xt);
+            left.xt[2 * n..2 * n + 2].copy_from_slice(&xt);
+            left.f[2 * n].copy_from_slice(&f[0..8]);
+            left.f[2 * n + 1].copy_from_slice(&f[8..16]);
+
+            /*
+             * The output of F in H(F(k1, y|i-1||j), r)
+             */
+            ro_prefix.evaluate_all(&mut ro_keys, |j, value| {
+                value[0..2].copy_from_slice(&(j as u16).to_be_bytes())
+            });
+            let hashes = hasher.hash_all(&mut ro_keys);
+
+            for (j, h) in hashes.iter().enumerate() {
+                let jstar = prp.invert(j as u16)?;
+                let indicator = cmp(jstar, xn);
+                right.data[2 * n + j / RightBlock4096::BITS]
+                    .set_bit(j % RightBlock4096::BITS, indicator ^ h);
+            }
+
+            prp_prefix.absorb(&xn.to_be_bytes());
+            ro_prefix.absorb(&xn.to_be_bytes());
+        }
+
+        Ok(CipherText { left, right })
+    }
+
+    fn decrypt_left<const N: usize>(&self, input: &Left<Self, N>) -> DecryptResult<N> {
+        #[allow(clippy::let_unit_value)]
+        let () = AssertEven::<N>::OK;
+
+        let mut x: PlainText<N> = [0; N];
+        let mut prp_prefix = PrefixPrf::new(&self.prefix.prp, &domain::<N>());
+
+        // Recover the blocks in order, rebuilding each PRP from the recovered prefix
+        for n in 0..(N / 2) {
+            let prp: KnuthShufflePRP16 = Prp::new(&prp_prefix.evaluate(&[]))?;
+            let xn = prp.invert(block_value(&input.xt, n))?.to_be_bytes();
+
+            x[2 * n..2 * n + 2].copy_from_slice(&xn);
+            prp_prefix.absorb(&xn);
+        }
+
+        // Check the PRF blocks to make sure the ciphertext was produced under these keys
+        let expected = self.encrypt_left(&x)?;
+        let mut is_valid = Choice::from(1);
+        for n in 0..N {
+            is_valid &= expected.f[n].ct_eq(&input.f[n]);
+        }
+
+        if bool::from(is_valid) {
+            Ok(x)
+        } else {
+            Err(OreError::DecryptFailed)
+        }
+    }
+
+    fn compare_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering> {
+        if a.len() != b.len() || a.len() < NONCE_SIZE {
+            return None;
+        };
+        let left_size = Self::LeftBlockType::BLOCK_SIZE;
+        let right_size = Self::RightBlockType::BLOCK_SIZE;
+        let num_blocks = (a.len() - NONCE_SIZE) / (left_size + right_size + 1);
+
+        Self::compare_left_raw_slices(&a[0..num_blocks * (left_size + 1)], b)
+    }
+
+    fn compare_left_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering> {
+        let left_size = Self::LeftBlockType::BLOCK_SIZE;
+        let right_size = Self::RightBlockType::BLOCK_SIZE;
+        let num_blocks = a.len() / (left_size + 1);
+
+        if a.len() != num_blocks * (left_size + 1)
+            || b.len() != num_blocks * (left_size + right_size + 1) + NONCE_SIZE
+            || num_blocks & 1 == 1
+        {
+            return None;
+        }
+
+        // Slices for the PRF ("f") blocks
+        let a_f = &a[num_blocks..];
+        let b_f = &b[num_blocks..];
+
+        let mut is_equal = Choice::from(1);
+        let mut l: u64 = 0; // Unequal 16-bit block
+
+        for n in 0..(num_blocks / 2) {
+            // Each 16-bit block spans 2 bytes of xt and 2 left blocks
+            let f_pos = 2 * n * left_size;
+            let condition: Choice = !a[2 * n..2 * n + 2].ct_eq(&b[2 * n..2 * n + 2])
+                | !a_f[f_pos..(f_pos + 16)].ct_eq(&b_f[f_pos..(f_pos + 16)]);
+
+            l.conditional_assign(&(n as u64), is_equal & condition);
+            is_equal.conditional_assign(&Choice::from(0), is_equal & condition);
+        }
+
+        if bool::from(is_equal) {
+            return Some(Ordering::Equal);
+        }
+
+        let l = l as usize;
+        let f_pos = 2 * l * left_size;
+
+        let b_right = &b[num_blocks * (left_size + 1)..];
+        let hash: Aes128Z2Hash = Hash::new(HashKey::from_slice(&b_right[0..NONCE_SIZE]));
+        let h = hash.hash(&a_f[f_pos..(f_pos + 16)]);
+
+        // Find the bit in the right ciphertext for the block (across 2 right blocks)
+        let bit = block_value(a, l) as usize;
+        let byte = b_right[NONCE_SIZE + 2 * l * right_size + bit / 8];
+        let test = ((byte >> (bit % 8)) & 1) ^ h;
+
+        if test == 1 {
+            return Some(Ordering::Greater);
+        }
+
+        Some(Ordering::Less)
+    }
+}
+
+impl<R: Rng + SeedableRng> OreEnvelopeScheme for OreAes128<R> {
+    const SCHEME_ID: u8 = 16;
+}
+
+fn cmp_left<R: Rng + SeedableRng, const N: usize>(
+    a: &Left<OreAes128<R>, N>,
+    b: &CipherText<OreAes128<R>, N>,
+) -> Ordering {
+    let mut is_equal = Choice::from(1);
+    let mut l: u64 = 0; // Unequal 16-bit block
+
+    for n in 0..(N / 2) {
+        let condition: Choice = !a.xt[2 * n..2 * n + 2].ct_eq(&b.left.xt[2 * n..2 * n + 2])
+            | !a.f[2 * n].ct_eq(&b.left.f[2 * n])
+            | !a.f[2 * n + 1].ct_eq(&b.left.f[2 * n + 1]);
+
+        l.conditional_assign(&(n as u64), is_equal & condition);
+        is_equal.conditional_assign(&Choice::from(0), is_equal & condition);
+    }
+
+    if bool::from(is_equal) {
+        return Ordering::Equal;
+    }
+
+    let l = l as usize;
+
+    let hash: Aes128Z2Hash = Hash::new(AesBlock::from_slice(&b.right.nonce));
+    let h = hash.hash(&join_halves(&a.f[2 * l], &a.f[2 * l + 1]));
+
+    let bit = block_value(&a.xt, l) as usize;
+    let test =
+        b.right.data[2 * l + bit / RightBlock4096::BITS].get_bit(bit % RightBlock4096::BITS) ^ h;
+    if test == 1 {
+        return Ordering::Greater;
+    }
+
+    Ordering::Less
+}
+
+impl<R: Rng + SeedableRng> OreCompare for OreAes128<R> {
+    fn compare_left<const N: usize>(a: &Left<Self, N>, b: &CipherText<Self, N>) -> Ordering {
+        cmp_left(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encrypt::OreEncrypt;
+
+    type Ore = OreAes128ChaCha20;
+
+    fn init_ore() -> Ore {
+        let mut k1: [u8; 16] = Default::default();
+        let mut k2: [u8; 16] = Default::default();
+
+        let mut rng = ChaCha20Rng::from_entropy();
+
+        rng.fill(&mut k1);
+        rng.fill(&mut k2);
+
+        OreCipher::init(&k1, &k2).unwrap()
+    }
+
+    /*
+     * Encryption with 16-bit blocks is slow so these tests use a handful of
+     * values instead of quickcheck
+     */
+    const VALUES: [u32; 6] = [0, 1, 0xff, 0x0100, 0x00ff_ffff, u32::MAX];
+
+    #[test]
+    fn compare_u32() {
+        let ore = init_ore();
+        let cts: Vec<_> = VALUES.iter().map(|x| x.encrypt(&ore).unwrap()).collect();
+
+        for (i, a) in cts.iter().enumerate() {
+            for (j, b) in cts.iter().enumerate() {
+                assert_eq!(a.cmp(b), VALUES[i].cmp(&VALUES[j]));
+            }
+        }
+    }
+
+    #[test]
+    fn compare_u32_raw_slices() {
+        let ore = init_ore();
+        let cts: Vec<_> = VALUES
+            .iter()
+            .map(|x| x.encrypt(&ore).unwrap().to_bytes())
+            .collect();
+
+        for (i, a) in cts.iter().enumerate() {
+            for (j, b) in cts.iter().enumerate() {
+                assert_eq!(
+                    Ore::compare_raw_slices(a, b),
+                    Some(VALUES[i].cmp(&VALUES[j]))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn compare_left_u32() {
+        let ore = init_ore();
+        let a = 0x0001_0000u32.encrypt_left(&ore).unwrap();
+        let b = 0x0000_ffffu32.encrypt(&ore).unwrap();
+        let c = 0x0001_0000u32.encrypt(&ore).unwrap();
+
+        assert!(a > b);
+        assert!(a == c);
+    }
+
+    #[test]
+    fn decrypt_u32() {
+        let ore = init_ore();
+        for x in VALUES {
+            let a = x.encrypt(&ore).unwrap();
+            assert_eq!(u32::decrypt(&a, &ore).unwrap(), x);
+        }
+    }
+
+    #[test]
+    fn binary_encoding() {
+        let ore = init_ore();
+        let a = 10u32.encrypt(&ore).unwrap();
+        let bin = a.to_bytes();
+        assert_eq!(a, CipherText::<Ore, 4>::from_slice(&bin).unwrap());
+    }
+
+    #[test]
+    fn init_with_seed_is_deterministic() {
+        let k1: [u8; 16] = rand::random();
+        let k2: [u8; 16] = rand::random();
+        let ore1 = Ore::init_with_seed(&k1, &k2, [7; 32]).unwrap();
+        let ore2 = Ore::init_with_seed(&k1, &k2, [7; 32]).unwrap();
+
+        let a = 1000u32.encrypt(&ore1).unwrap().to_bytes();
+        let b = 1000u32.encrypt(&ore2).unwrap().to_bytes();
+
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,124 @@
+use subtle_ng::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
+
+use crate::ciphertext::{CipherTextBlock, ParseError};
+
+/*
+ * Block type for a Left CipherText with 8-bytes per plaintext byte.
+ * Each 16-bit block spans 2 plaintext bytes so its 16-byte PRF output is split over 2 of these.
+ */
+#[derive(Debug, Copy, Clone, Default)]
+pub struct LeftBlock8 {
+    data: [u8; 8],
+}
+
+impl LeftBlock8 {
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data
+    }
+
+    #[inline]
+    pub fn copy_from_slice(&mut self, data: &[u8]) {
+        self.data.copy_from_slice(data);
+    }
+}
+
+impl ConstantTimeEq for LeftBlock8 {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.data.ct_eq(&other.data)
+    }
+}
+
+/*
+ * Block type for a Right CipherText with 4096-bytes per plaintext byte
+ * corresponding to half of a 16-bit plaintext block with a 2-bit indicator function
+ * (65536 bits per block).
+ */
+#[derive(Debug, Copy, Clone)]
+pub struct RightBlock4096 {
+    data: [u8; 4096],
+}
+
+impl Default for RightBlock4096 {
+    fn default() -> Self {
+        Self { data: [0; 4096] }
+    }
+}
+
+impl RightBlock4096 {
+    pub const BITS: usize = 32768;
+
+    #[inline]
+    pub fn set_bit(&mut self, bit: usize, value: u8) {
+        debug_assert!(bit < Self::BITS);
+        let byte_index = bit / 8;
+        let mask = bit % 8;
+        let v = value << mask;
+        self.data[byte_index] |= v;
+    }
+
+    #[inline]
+    pub fn get_bit(&self, bit: usize) -> u8 {
+        debug_assert!(bit < Self::BITS);
+        let byte_index = bit / 8;
+        let position = bit % 8;
+        let v = 1 << position;
+
+        (self.data[byte_index] & v) >> position
+    }
+}
+
+impl CipherTextBlock for LeftBlock8 {
+    const BLOCK_SIZE: usize = 8;
+
+    fn to_bytes(self) -> Vec<u8> {
+        self.data.to_vec()
+    }
+
+    fn from_bytes(data: &[u8]) -> Result<Self, ParseError> {
+        ParseError::check_length(Self::BLOCK_SIZE, data.len())?;
+        let mut out = Self::default();
+        out.copy_from_slice(data);
+        Ok(out)
+    }
+
+    fn default_in_place(&mut self) {
+        self.data.zeroize()
+    }
+}
+
+impl CipherTextBlock for RightBlock4096 {
+    const BLOCK_SIZE: usize = 4096;
+
+    fn to_bytes(self) -> Vec<u8> {
+        self.data.to_vec()
+    }
+
+    fn from_bytes(data: &[u8]) -> Result<Self, ParseError> {
+        ParseError::check_length(Self::BLOCK_SIZE, data.len())?;
+        let mut out = Self::default();
+        out.data.copy_from_slice(data);
+        Ok(out)
+    }
+
+    fn default_in_place(&mut self) {
+        self.data.zeroize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get_bit() {
+        let mut block: RightBlock4096 = Default::default();
+        block.set_bit(17, 1);
+        assert_eq!(block.get_bit(17), 1);
+        assert_eq!(block.get_bit(18), 0);
+
+        block.set_bit(RightBlock4096::BITS - 1, 1);
+        assert_eq!(block.get_bit(RightBlock4096::BITS - 1), 1);
+    }
+}
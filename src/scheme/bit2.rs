@@ -4,15 +4,11 @@
 
 use crate::{
     ciphertext::*,
-    primitives::{
-        hash::Aes128Z2Hash, prf::Aes128Prf, prp::KnuthShufflePRP, AesBlock, Hash, HashKey, Prf,
-        Prp, NONCE_SIZE,
-    },
-    OreCipher, OreError, PlainText,
+    primitives::{hash::Aes128Z2Hash, AesBlock, Hash, HashKey, NONCE_SIZE},
+    scheme::prefixed::{IndicatorBlock, IndicatorKeys},
+    OreCipher, OreCompare, OreError, PlainText,
 };
 
-use aes::cipher::generic_array::GenericArray;
-use lazy_static::lazy_static;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use std::cell::RefCell;
@@ -21,13 +17,13 @@ use subtle_ng::{Choice, ConditionallySelectable, ConstantTimeEq};
 use zeroize::ZeroizeOnDrop;
 
 pub mod block_types;
+mod variable;
 pub use self::block_types::*;
 
 /* Define our scheme */
 #[derive(Debug, ZeroizeOnDrop)]
 pub struct OreAes128<R: Rng + SeedableRng> {
-    prf1: Aes128Prf,
-    prf2: Aes128Prf,
+    keys: IndicatorKeys,
     #[zeroize(skip)]
     rng: RefCell<R>,
 }
@@ -37,63 +33,61 @@ pub type OreAes128ChaCha20 = OreAes128<ChaCha20Rng>;
 /* Define some convenience types */
 type EncryptLeftResult<R, const N: usize> = Result<Left<OreAes128<R>, N>, OreError>;
 type EncryptResult<R, const N: usize> = Result<CipherText<OreAes128<R>, N>, OreError>;
+type DecryptResult<const N: usize> = Result<PlainText<N>, OreError>;
+
+/* Separates the PrefixPrf outputs from other schemes and plaintext lengths */
+fn domain<const N: usize>() -> [u8; 8] {
+    let mut domain = *b"bit2\0\0\0\0";
+    domain[4..].copy_from_slice(&(N as u32).to_be_bytes());
+    domain
+}
 
 fn cmp(a: u8, b: u8) -> u8 {
     u8::from(a > b)
 }
 
-impl<R: Rng + SeedableRng> OreCipher for OreAes128<R> {
-    type LeftBlockType = LeftBlock16;
-    type RightBlockType = RightBlock32;
+impl IndicatorBlock for RightBlock32 {
+    type Hasher = Aes128Z2Hash;
 
-    fn init(k1: &[u8; 16], k2: &[u8; 16]) -> Result<Self, OreError> {
-        // TODO: k1 and k2 should be Key types and we should have a set of traits to abstract the
-        // behaviour ro parsing/loading etc
+    fn indicator(jstar: u8, x: u8) -> u8 {
+        cmp(jstar, x)
+    }
 
-        let rng: R = SeedableRng::from_entropy();
+    fn set_masked(&mut self, j: usize, indicator: u8, h: u8) {
+        self.set_bit(j, indicator ^ h);
+    }
+}
 
-        return Ok(OreAes128 {
-            prf1: Prf::new(GenericArray::from_slice(k1)),
-            prf2: Prf::new(GenericArray::from_slice(k2)),
+impl<R: Rng + SeedableRng> OreAes128<R> {
+    /// Initialize a cipher which uses the given RNG to generate nonces for right ciphertexts.
+    ///
+    /// Ciphers initialized with the same keys and identical RNGs produce byte-identical ciphertexts
+    /// which is useful for test vectors. Use [`OreCipher::init`] everywhere else.
+    pub fn init_with_rng(k1: &[u8; 16], k2: &[u8; 16], rng: R) -> Result<Self, OreError> {
+        Ok(OreAes128 {
+            keys: IndicatorKeys::new(k1, k2),
             rng: RefCell::new(rng),
-        });
+        })
     }
 
-    fn encrypt_left<const N: usize>(&self, x: &PlainText<N>) -> EncryptLeftResult<R, N> {
-        let mut output = Left::<Self, N>::init();
-
-        // Build the prefixes
-        // TODO: Don't modify struct values directly - use a function on a "Left" trait
-        output.f.iter_mut().enumerate().for_each(|(n, block)| {
-            block[0..n].clone_from_slice(&x[0..n]);
-            // TODO: Include the block number in the prefix to avoid repeating values for common
-            // blocks in a long prefix
-            // e.g. when plaintext is 4700 (2-bytes/blocks)
-            // xt = [17, 17, 17, 17, 17, 17, 223, 76]
-        });
-
-        self.prf2.encrypt_all(&mut output.f);
-
-        for (n, xn) in x.iter().enumerate().take(N) {
-            // Set prefix and create PRP for the block
-            let prp: KnuthShufflePRP<u8, 256> = Prp::new(&output.f[n])?;
+    /// Initialize a cipher with an RNG created from the given seed (see [`Self::init_with_rng`]).
+    pub fn init_with_seed(k1: &[u8; 16], k2: &[u8; 16], seed: R::Seed) -> Result<Self, OreError> {
+        Self::init_with_rng(k1, k2, R::from_seed(seed))
+    }
+}
 
-            output.xt[n] = prp.permute(*xn)?;
-        }
+impl<R: Rng + SeedableRng> OreCipher for OreAes128<R> {
+    type LeftBlockType = LeftBlock16;
+    type RightBlockType = RightBlock32;
 
-        // Reset the f block
-        // We don't actually need to clear sensitive data here, we
-        // just need fast "zero set". Reassigning the value will drop the old one and allocate new
-        // data to the stack
-        output.f = [Default::default(); N];
+    fn init(k1: &[u8; 16], k2: &[u8; 16]) -> Result<Self, OreError> {
+        Self::init_with_rng(k1, k2, SeedableRng::from_entropy())
+    }
 
-        for n in 0..N {
-            output.f[n][0..n].clone_from_slice(&x[0..n]);
-            output.f[n][n] = output.xt[n];
-            // Include the block number in the value passed to the Random Oracle
-            output.f[n][N] = n as u8;
-        }
-        self.prf1.encrypt_all(&mut output.f);
+    fn encrypt_left<const N: usize>(&self, x: &PlainText<N>) -> EncryptLeftResult<R, N> {
+        let mut output = Left::<Self, N>::init();
+        self.keys
+            .encrypt_left(&domain::<N>(), x, &mut output.xt, &mut output.f)?;
 
         Ok(output)
     }
@@ -105,79 +99,24 @@ impl<R: Rng + SeedableRng> OreCipher for OreAes128<R> {
         // Generate a 16-byte random nonce
         self.rng.borrow_mut().try_fill(&mut right.nonce)?;
 
-        // Build the prefixes
-        // TODO: Don't modify struct values directly - use a function on a "Left"
-        left.f.iter_mut().enumerate().for_each(|(n, block)| {
-            block[0..n].clone_from_slice(&x[0..n]);
-        });
-
-        self.prf2.encrypt_all(&mut left.f);
-
-        // To make zeroizing / resetting the RO keys
-        // Since the AesBlock type is stack allocated this should get optimised to a single memcpy
-        lazy_static! {
-            static ref ZEROED_RO_KEYS: [AesBlock; 256] = [Default::default(); 256];
-        }
-
-        let mut ro_keys = *ZEROED_RO_KEYS;
-
-        for n in 0..N {
-            // Set prefix and create PRP for the block
-            let prp: KnuthShufflePRP<u8, 256> = Prp::new(&left.f[n])?;
-
-            left.xt[n] = prp.permute(x[n])?;
-
-            // Reset the f block
-            left.f[n].default_in_place();
-
-            left.f[n][0..n].clone_from_slice(&x[0..n]);
-            left.f[n][n] = left.xt[n];
-            // Include the block number in the value passed to the Random Oracle
-            left.f[n][N] = n as u8;
-
-            for (j, ro_key) in ro_keys.iter_mut().enumerate() {
-                /*
-                 * The output of F in H(F(k1, y|i-1||j), r)
-                 */
-                ro_key[0..n].clone_from_slice(&x[0..n]);
-                ro_key[n] = j as u8;
-                ro_key[N] = n as u8;
-            }
-
-            self.prf1.encrypt_all(&mut ro_keys);
-
-            /* TODO: This seems to work but it is technically using the nonce as the key
-             * (instead of using it as the plaintext). This appears to be how the original
-             * ORE implementation does it but it feels a bit wonky to me. Should check with David.
-             * It is useful though because the AES crate makes it easy to encrypt groups of 8
-             * plaintexts under the same key. We really want the ability to encrypt the same
-             * plaintext (i.e. the nonce) under different keys but this may be an acceptable
-             * approximation.
-             *
-             * If not, we will probably need to implement our own parallel encrypt using intrisics
-             * like in the AES crate: https://github.com/RustCrypto/block-ciphers/blob/master/aes/src/ni/aes128.rs#L26
-             */
-            let hasher: Aes128Z2Hash = Hash::new(AesBlock::from_slice(&right.nonce));
-            let hashes = hasher.hash_all(&mut ro_keys);
-
-            // FIXME: force casting to u8 from usize could cause a panic
-            for (j, h) in hashes.iter().enumerate() {
-                let jstar = prp.invert(j as u8)?;
-                let indicator = cmp(jstar, x[n]);
-                right.data[n].set_bit(j, indicator ^ h);
-            }
-
-            // Zeroize / reset the RO keys before the next loop iteration
-            ro_keys.clone_from_slice(&*ZEROED_RO_KEYS);
-        }
-
-        self.prf1.encrypt_all(&mut left.f);
+        self.keys.encrypt(
+            &domain::<N>(),
+            x,
+            &mut left.xt,
+            &mut left.f,
+            &right.nonce,
+            &mut right.data,
+        )?;
 
         Ok(CipherText { left, right })
     }
 
+    fn decrypt_left<const N: usize>(&self, input: &Left<Self, N>) -> DecryptResult<N> {
+        self.keys.decrypt_left(&domain::<N>(), &input.xt, &input.f)
+    }
+
     fn compare_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering> {
-        if a.len() != b.len() {
+        if a.len() != b.len() || a.len() < NONCE_SIZE {
             return None;
         };
         let left_size = Self::LeftBlockType::BLOCK_SIZE;
@@ -187,6 +126,22 @@ impl<R: Rng + SeedableRng> OreCipher for OreAes128<R> {
         // first byte?
         let num_blocks = (a.len() - NONCE_SIZE) / (left_size + right_size + 1);
 
+        // The left ciphertext is always at the start of the full ciphertext
+        Self::compare_left_raw_slices(&a[0..num_blocks * (left_size + 1)], b)
+    }
+
+    fn compare_left_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering> {
+        let left_size = Self::LeftBlockType::BLOCK_SIZE;
+        let right_size = Self::RightBlockType::BLOCK_SIZE;
+
+        let num_blocks = a.len() / (left_size + 1);
+
+        if a.len() != num_blocks * (left_size + 1)
+            || b.len() != num_blocks * (left_size + right_size + 1) + NONCE_SIZE
+        {
+            return None;
+        }
+
         let mut is_equal = Choice::from(1);
         let mut l: u64 = 0; // Unequal block
 
@@ -225,6 +180,10 @@ impl<R: Rng + SeedableRng> OreCipher for OreAes128<R> {
     }
 }
 
+impl<R: Rng + SeedableRng> OreEnvelopeScheme for OreAes128<R> {
+    const SCHEME_ID: u8 = 2;
+}
+
 // TODO: Move these to block_types
 #[inline]
 fn left_block(input: &[u8], n: usize) -> &[u8] {
@@ -249,60 +208,57 @@ fn get_bit(block: &[u8], bit: usize) -> u8 {
     (block[byte_index] & v) >> position
 }
 
-impl<const N: usize> PartialEq for CipherText<OreAes128ChaCha20, N> {
-    fn eq(&self, b: &Self) -> bool {
-        matches!(self.cmp(b), Ordering::Equal)
+/*
+ * Compare a left ciphertext against a full ciphertext.
+ *
+ * Equality is detected by comparing left blocks so the 2-bit indicator scheme always
+ * needs the left half of `b` (a left can't be compared against a right on its own).
+ */
+fn cmp_left<R: Rng + SeedableRng, const N: usize>(
+    a: &Left<OreAes128<R>, N>,
+    b: &CipherText<OreAes128<R>, N>,
+) -> Ordering {
+    let mut is_equal = Choice::from(1);
+    let mut l: u64 = 0; // Unequal block
+
+    for n in 0..N {
+        let condition: Choice = !(a.xt[n].ct_eq(&b.left.xt[n])) | !(a.f[n].ct_eq(&b.left.f[n]));
+
+        l.conditional_assign(&(n as u64), is_equal & condition);
+        is_equal.conditional_assign(&Choice::from(0), is_equal & condition);
     }
-}
 
-impl<const N: usize> Ord for CipherText<OreAes128ChaCha20, N> {
-    fn cmp(&self, b: &Self) -> Ordering {
-        let mut is_equal = Choice::from(1);
-        let mut l: u64 = 0; // Unequal block
-
-        for n in 0..N {
-            let condition: Choice =
-                !(self.left.xt[n].ct_eq(&b.left.xt[n])) | !(self.left.f[n].ct_eq(&b.left.f[n]));
-
-            l.conditional_assign(&(n as u64), is_equal & condition);
-            is_equal.conditional_assign(&Choice::from(0), is_equal & condition);
-        }
+    let l: usize = l as usize;
 
-        let l: usize = l as usize;
-
-        if bool::from(is_equal) {
-            return Ordering::Equal;
-        }
-
-        let hash: Aes128Z2Hash = Hash::new(AesBlock::from_slice(&b.right.nonce));
-        let h = hash.hash(&self.left.f[l]);
+    if bool::from(is_equal) {
+        return Ordering::Equal;
+    }
 
-        let test = b.right.data[l].get_bit(self.left.xt[l] as usize) ^ h;
-        if test == 1 {
-            return Ordering::Greater;
-        }
+    let hash: Aes128Z2Hash = Hash::new(AesBlock::from_slice(&b.right.nonce));
+    let h = hash.hash(&a.f[l]);
 
-        Ordering::Less
+    let test = b.right.data[l].get_bit(a.xt[l] as usize) ^ h;
+    if test == 1 {
+        return Ordering::Greater;
     }
+
+    Ordering::Less
 }
 
-impl<const N: usize> PartialOrd for CipherText<OreAes128ChaCha20, N> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl<R: Rng + SeedableRng> OreCompare for OreAes128<R> {
+    fn compare_left<const N: usize>(a: &Left<Self, N>, b: &CipherText<Self, N>) -> Ordering {
+        cmp_left(a, b)
     }
 }
 
-/*
- * (From the Rust docs)
- * This property cannot be checked by the compiler, and therefore Eq implies PartialEq, and has no extra methods.
- */
-impl<const N: usize> Eq for CipherText<OreAes128ChaCha20, N> {}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::encrypt::OreEncrypt;
+    use crate::encrypt::{OreEncrypt, OreEncryptFloat};
+    use crate::{Descending, FloatPolicy, NullsLast, OrePlaintext};
+    use hex_literal::hex;
     use quickcheck::TestResult;
+    use rand::rngs::StdRng;
 
     type Ore = OreAes128ChaCha20;
 
@@ -360,6 +316,45 @@ mod tests {
             matches!(Ore::compare_raw_slices(&a, &b), Some(Ordering::Equal))
         }
 
+        fn compare_u64_std_rng(x: u64, y: u64) -> bool {
+            let k1: [u8; 16] = rand::random();
+            let k2: [u8; 16] = rand::random();
+            let ore: OreAes128<StdRng> = OreCipher::init(&k1, &k2).unwrap();
+            let mut sorted = [x.encrypt(&ore).unwrap(), y.encrypt(&ore).unwrap()];
+            sorted.sort();
+
+            match x.cmp(&y) {
+                Ordering::Greater => u64::decrypt(&sorted[0], &ore).unwrap() == y,
+                Ordering::Less    => u64::decrypt(&sorted[0], &ore).unwrap() == x,
+                Ordering::Equal   => sorted[0] == sorted[1]
+            }
+        }
+
+        fn compare_left_u64(x: u64, y: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt_left(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_left_u64_raw_slices(x: u64, y: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt_left(&ore).unwrap().to_bytes();
+            let b = y.encrypt(&ore).unwrap().to_bytes();
+
+            match Ore::compare_left_raw_slices(&a, &b) {
+                Some(Ordering::Greater) => x > y,
+                Some(Ordering::Less)    => x < y,
+                Some(Ordering::Equal)   => x == y,
+                None                    => false
+            }
+        }
+
         fn compare_u32(x: u32, y: u32) -> bool {
             let ore = init_ore();
             let a = x.encrypt(&ore).unwrap();
@@ -380,11 +375,197 @@ mod tests {
             a == b
         }
 
+        fn compare_i64(x: i64, y: i64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_i32(x: i32, y: i32) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_i16(x: i16, y: i16) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_i8(x: i8, y: i8) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_i64_raw_slices(x: i64, y: i64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap().to_bytes();
+            let b = y.encrypt(&ore).unwrap().to_bytes();
+
+            Ore::compare_raw_slices(&a, &b) == Some(x.cmp(&y))
+        }
+
+        fn decrypt_i64(x: i64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+
+            i64::decrypt(&a, &ore).unwrap() == x
+        }
+
+        fn compare_u128(x: u128, y: u128) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_u128_raw_slices(x: u128, y: u128) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap().to_bytes();
+            let b = y.encrypt(&ore).unwrap().to_bytes();
+
+            Ore::compare_raw_slices(&a, &b) == Some(x.cmp(&y))
+        }
+
+        fn compare_left_u128(x: u128, y: u128) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt_left(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_i128(x: i128, y: i128) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn decrypt_u128(x: u128) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+
+            u128::decrypt(&a, &ore).unwrap() == x
+        }
+
+        fn decrypt_i128(x: i128) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt_left(&ore).unwrap();
+
+            i128::decrypt_left(&a, &ore).unwrap() == x
+        }
+
+        fn decode_decrypted_plaintext(x: i64, y: (u32, f64)) -> bool {
+            let ore = init_ore();
+            let a = ore.decrypt(&x.encrypt(&ore).unwrap()).unwrap();
+            let b = ore.decrypt_left(&y.encrypt_left(&ore).unwrap()).unwrap();
+
+            a == x.encode() && i64::decode(&a).unwrap() == x
+                && <(u32, f64)>::decode(&b).unwrap().0 == y.0
+                && <(u32, f64)>::decode(&b).unwrap().1.to_bits() == y.1.to_bits()
+        }
+
         fn compare_f64(x: f64, y: f64) -> TestResult {
             if x.is_nan() || x.is_infinite() || y.is_nan() || y.is_infinite() {
                 return TestResult::discard();
             }
 
+            /* -0.0 and 0.0 are equal but encrypt differently under FloatPolicy::Raw */
+            if x == 0.0 && y == 0.0 {
+                return TestResult::discard();
+            }
+
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.partial_cmp(&y) {
+                Some(Ordering::Greater) => TestResult::from_bool(a > b),
+                Some(Ordering::Less)    => TestResult::from_bool(a < b),
+                Some(Ordering::Equal)   => TestResult::from_bool(a == b),
+                None                    => TestResult::failed()
+            }
+        }
+
+        fn compare_f64_canonicalized(x: f64, y: f64) -> bool {
+            let ore = init_ore();
+            let policy = FloatPolicy::Canonicalize;
+            let a = x.encrypt_with_policy(&ore, policy).unwrap();
+            let b = y.encrypt_with_policy(&ore, policy).unwrap();
+
+            match x.partial_cmp(&y) {
+                Some(Ordering::Greater) => a > b,
+                Some(Ordering::Less)    => a < b,
+                Some(Ordering::Equal)   => a == b,
+                None if x.is_nan() && y.is_nan() => a == b,
+                None if x.is_nan() => a > b,
+                None => a < b
+            }
+        }
+
+        fn encrypt_f64_rejected(x: f64) -> bool {
+            let ore = init_ore();
+            let full = x.encrypt_with_policy(&ore, FloatPolicy::Reject);
+            let left = x.encrypt_left_with_policy(&ore, FloatPolicy::Reject);
+
+            if x.is_finite() {
+                full.unwrap() == (x + 0.0).encrypt(&ore).unwrap() && left.is_ok()
+            } else {
+                matches!(full, Err(OreError::InvalidFloat)) && matches!(left, Err(OreError::InvalidFloat))
+            }
+        }
+
+        fn compare_f32(x: f32, y: f32) -> TestResult {
+            if x.is_nan() || y.is_nan() {
+                return TestResult::discard();
+            }
+
+            /* -0.0 and 0.0 are equal but encrypt differently under FloatPolicy::Raw */
+            if x == 0.0 && y == 0.0 {
+                return TestResult::discard();
+            }
+
             let ore = init_ore();
             let a = x.encrypt(&ore).unwrap();
             let b = y.encrypt(&ore).unwrap();
@@ -409,6 +590,172 @@ mod tests {
             a == b
         }
 
+        fn decrypt_u64(x: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+
+            u64::decrypt(&a, &ore).unwrap() == x
+        }
+
+        fn decrypt_left_u64(x: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt_left(&ore).unwrap();
+
+            u64::decrypt_left(&a, &ore).unwrap() == x
+        }
+
+        fn decrypt_u32(x: u32) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+
+            u32::decrypt(&a, &ore).unwrap() == x
+        }
+
+        /*
+         * Compare the bits so that NaNs round-trip, too
+         */
+        fn decrypt_f64(x: f64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+
+            f64::decrypt(&a, &ore).unwrap().to_bits() == x.to_bits()
+        }
+
+        fn decrypt_f32(x: f32) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt_left(&ore).unwrap();
+
+            f32::decrypt_left(&a, &ore).unwrap().to_bits() == x.to_bits()
+        }
+
+        fn compare_u32_u64_pair(x: (u32, u64), y: (u32, u64)) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_u8_u16_pair(x: (u8, u16), y: (u8, u16)) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            a.cmp(&b) == x.cmp(&y)
+        }
+
+        fn compare_i64_f64_pair(x: (i64, f64), y: (i64, f64)) -> TestResult {
+            if x.1.is_nan() || y.1.is_nan() {
+                return TestResult::discard();
+            }
+
+            let ore = init_ore();
+            let a = x.encrypt_left(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.0.cmp(&y.0).then(x.1.total_cmp(&y.1)) {
+                Ordering::Greater => TestResult::from_bool(a > b),
+                Ordering::Less    => TestResult::from_bool(a < b),
+                Ordering::Equal   => TestResult::from_bool(a == b)
+            }
+        }
+
+        fn decrypt_pair(x: (i128, u32)) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+
+            <(i128, u32)>::decrypt(&a, &ore).unwrap() == x
+        }
+
+        fn compare_descending_u64(x: u64, y: u64) -> bool {
+            let ore = init_ore();
+            let a = Descending(x).encrypt(&ore).unwrap();
+            let b = Descending(y).encrypt(&ore).unwrap();
+
+            match y.cmp(&x) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_descending_i32_raw_slices(x: i32, y: i32) -> bool {
+            let ore = init_ore();
+            let a = Descending(x).encrypt(&ore).unwrap().to_bytes();
+            let b = Descending(y).encrypt(&ore).unwrap().to_bytes();
+
+            Ore::compare_raw_slices(&a, &b) == Some(y.cmp(&x))
+        }
+
+        fn compare_descending_pair(x: (u32, f64), y: (u32, f64)) -> TestResult {
+            if x.1.is_nan() || y.1.is_nan() {
+                return TestResult::discard();
+            }
+
+            let ore = init_ore();
+            let a = Descending(x).encrypt_left(&ore).unwrap();
+            let b = Descending(y).encrypt(&ore).unwrap();
+
+            match y.0.cmp(&x.0).then(y.1.total_cmp(&x.1)) {
+                Ordering::Greater => TestResult::from_bool(a > b),
+                Ordering::Less    => TestResult::from_bool(a < b),
+                Ordering::Equal   => TestResult::from_bool(a == b)
+            }
+        }
+
+        fn decrypt_descending_i64(x: i64) -> bool {
+            let ore = init_ore();
+            let a = Descending(x).encrypt(&ore).unwrap();
+
+            Descending::<i64>::decrypt(&a, &ore).unwrap() == Descending(x)
+        }
+
+        fn descending_plaintext_is_complement(x: u32) -> bool {
+            let ore = init_ore();
+            let a = Descending(x.to_be_bytes()).encrypt_left(&ore).unwrap();
+
+            ore.decrypt_left(&a).unwrap() == (!x).to_be_bytes()
+        }
+
+        fn compare_option_u64(x: Option<u64>, y: Option<u64>) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_nulls_last_i32_raw_slices(x: Option<i32>, y: Option<i32>) -> bool {
+            let ore = init_ore();
+            let (x, y) = (NullsLast(x), NullsLast(y));
+            let a = x.encrypt(&ore).unwrap().to_bytes();
+            let b = y.encrypt(&ore).unwrap().to_bytes();
+
+            Ore::compare_raw_slices(&a, &b) == Some(x.cmp(&y))
+        }
+
+        fn decrypt_option_i64(x: Option<i64>) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+
+            Option::<i64>::decrypt(&a, &ore).unwrap() == x
+        }
+
+        fn decrypt_nulls_last_u32(x: Option<u32>) -> bool {
+            let ore = init_ore();
+            let a = NullsLast(x).encrypt_left(&ore).unwrap();
+
+            NullsLast::<u32>::decrypt_left(&a, &ore).unwrap() == NullsLast(x)
+        }
+
         fn compare_plaintext(x: u64, y: u64) -> bool {
             let ore = init_ore();
             let a = x.to_be_bytes().encrypt(&ore).unwrap();
@@ -466,6 +813,75 @@ mod tests {
         assert!(a == b);
     }
 
+    #[test]
+    fn negative_to_positive() {
+        let ore = init_ore();
+        let a = (-1i64).encrypt(&ore).unwrap();
+        let b = 0i64.encrypt(&ore).unwrap();
+        let c = i64::MIN.encrypt(&ore).unwrap();
+
+        assert!(a < b);
+        assert!(c < a);
+    }
+
+    #[test]
+    fn comparisons_in_last_block_u128() {
+        let ore = init_ore();
+        let a = (u128::MAX - 1).encrypt(&ore).unwrap();
+        let b = u128::MAX.encrypt(&ore).unwrap();
+        let c = i128::MIN.encrypt(&ore).unwrap();
+        let d = i128::MAX.encrypt(&ore).unwrap();
+
+        assert!(a < b);
+        assert!(c < d);
+    }
+
+    #[test]
+    fn pair_orders_by_first_component() {
+        let ore = init_ore();
+        let a = (1u32, u64::MAX).encrypt(&ore).unwrap();
+        let b = (2u32, 0u64).encrypt(&ore).unwrap();
+        let c = (2u32, 1u64).encrypt(&ore).unwrap();
+
+        assert_eq!(a.to_bytes().len(), CipherText::<Ore, 12>::size());
+        assert!(a < b);
+        assert!(b < c);
+    }
+
+    #[test]
+    fn nulls_first_and_last() {
+        let ore = init_ore();
+        let null = None::<i64>.encrypt(&ore).unwrap();
+        let min = Some(i64::MIN).encrypt(&ore).unwrap();
+        assert!(null < min);
+
+        let null = NullsLast(None::<f64>).encrypt(&ore).unwrap();
+        let inf = NullsLast(Some(f64::INFINITY)).encrypt(&ore).unwrap();
+        assert!(null > inf);
+
+        let null = Descending(None::<u32>).encrypt(&ore).unwrap();
+        let zero = Descending(Some(0u32)).encrypt(&ore).unwrap();
+        assert!(null > zero);
+        assert_eq!(zero.to_bytes().len(), CipherText::<Ore, 5>::size());
+    }
+
+    #[test]
+    fn null_with_padding_fails_to_decode() {
+        assert_eq!(Option::<u32>::decode(&[0, 0, 0, 0, 0]).unwrap(), None);
+        assert!(matches!(
+            Option::<u32>::decode(&[0, 0, 0, 0, 1]),
+            Err(OreError::DecryptFailed)
+        ));
+        assert!(matches!(
+            NullsLast::<u32>::decode(&[1, 0, 0, 0, 0x80]),
+            Err(OreError::DecryptFailed)
+        ));
+        assert!(matches!(
+            Option::<i16>::decode(&[2, 0, 0]),
+            Err(OreError::DecryptFailed)
+        ));
+    }
+
     #[test]
     fn comparisons_in_first_block() {
         let ore = init_ore();
@@ -495,6 +911,24 @@ mod tests {
         assert_eq!(Ore::compare_raw_slices(&a_64, &a_32), Option::None);
     }
 
+    #[test]
+    fn compare_left_raw_slices_mismatched_lengths() {
+        let ore = init_ore();
+        let a_64 = 10u64.encrypt_left(&ore).unwrap().to_bytes();
+        let a_32 = 10u32.encrypt(&ore).unwrap().to_bytes();
+
+        assert_eq!(Ore::compare_left_raw_slices(&a_64, &a_32), Option::None);
+    }
+
+    #[test]
+    fn compare_left_raw_slices_with_full_ciphertext() {
+        let ore = init_ore();
+        let a = 10u64.encrypt(&ore).unwrap().to_bytes();
+        let b = 10u64.encrypt(&ore).unwrap().to_bytes();
+
+        assert_eq!(Ore::compare_left_raw_slices(&a, &b), Option::None);
+    }
+
     #[test]
     fn binary_encoding() {
         let ore = init_ore();
@@ -513,6 +947,18 @@ mod tests {
         CipherText::<OreAes128ChaCha20, 8>::from_slice(&bin).unwrap();
     }
 
+    #[test]
+    fn decrypt_with_wrong_keys() {
+        let ore1 = init_ore();
+        let ore2 = init_ore();
+        let a = 1000u32.encrypt(&ore1).unwrap();
+
+        assert!(matches!(
+            u32::decrypt(&a, &ore2),
+            Err(OreError::DecryptFailed)
+        ));
+    }
+
     #[test]
     fn test_different_prf_keys() {
         let k1: [u8; 16] = [
@@ -554,4 +1000,38 @@ mod tests {
 
         assert_ne!(Some(Ordering::Equal), Ore::compare_raw_slices(&a, &b));
     }
+
+    #[test]
+    fn init_with_seed_is_deterministic() {
+        let k1: [u8; 16] = rand::random();
+        let k2: [u8; 16] = rand::random();
+        let ore1 = Ore::init_with_seed(&k1, &k2, [7; 32]).unwrap();
+        let ore2 = Ore::init_with_seed(&k1, &k2, [7; 32]).unwrap();
+
+        let a = 1000u32.encrypt(&ore1).unwrap().to_bytes();
+        let b = 1000u32.encrypt(&ore2).unwrap().to_bytes();
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn init_with_seed_known_answer() {
+        let k1: [u8; 16] = hex!("00010203 04050607 08090a0b 0c0d0e0f");
+        let k2: [u8; 16] = hex!("d0d007a5 3f9a6848 83bc1f21 0f6595a3");
+        let ore = Ore::init_with_seed(&k1, &k2, [0; 32]).unwrap();
+
+        let expected = hex!(
+            "fcc0"
+            "1d4eadfe80200b6f2121b60c40ca0fd8"
+            "914df2617e6c9027c38985823079effe"
+            "76b8e0ada0f13d90405d6ae55386bd28"
+            "b228d6ff2d58f47e5c6b70f78b7b19768b2350bcd50e2ecf1ea90d7467e1791c"
+            "ac8a442622c50b883ac40680b66490531c7c897c0437d16bf153bea1e6106b0b"
+        );
+
+        assert_eq!(
+            1000u16.to_be_bytes().encrypt(&ore).unwrap().to_bytes(),
+            expected
+        );
+    }
 }
@@ -44,11 +44,8 @@ impl CipherTextBlock for LeftBlock16 {
     }
 
     fn from_bytes(data: &[u8]) -> Result<Self, ParseError> {
-        if data.len() != Self::BLOCK_SIZE {
-            Err(ParseError)
-        } else {
-            Ok(Self::clone_from_slice(data))
-        }
+        ParseError::check_length(Self::BLOCK_SIZE, data.len())?;
+        Ok(Self::clone_from_slice(data))
     }
 
     fn default_in_place(&mut self) {
@@ -65,14 +62,11 @@ impl CipherTextBlock for RightBlock32 {
     }
 
     fn from_bytes(data: &[u8]) -> Result<Self, ParseError> {
-        if data.len() != Self::BLOCK_SIZE {
-            Err(ParseError)
-        } else {
-            let mut arr = [0; 32];
-            arr.clone_from_slice(data);
-
-            Ok(Self { data: arr })
-        }
+        ParseError::check_length(Self::BLOCK_SIZE, data.len())?;
+        let mut arr = [0; 32];
+        arr.clone_from_slice(data);
+
+        Ok(Self { data: arr })
     }
 
     fn default_in_place(&mut self) {
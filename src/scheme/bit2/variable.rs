@@ -0,0 +1,345 @@
+/*
+ * Variable length plaintexts (strings and byte slices) for the 2-bit indicator scheme.
+ *
+ * Every byte of the plaintext is encrypted as one block with the keys derived from a PrefixPrf
+ * over the preceding bytes. The PrefixPrf domain doesn't include the length of the plaintext so
+ * the blocks of two plaintexts with a common prefix match up to where they first differ.
+ */
+
+use super::{get_bit, left_block, right_block, LeftBlock16, OreAes128, RightBlock32};
+use crate::{
+    ciphertext::*,
+    primitives::{hash::Aes128Z2Hash, AesBlock, Hash, HashKey, NONCE_SIZE},
+    OreError, VarOreCipher,
+};
+
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use subtle_ng::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+const VAR_DOMAIN: &[u8] = b"bit2-var";
+
+type VarLeftResult<R> = Result<VarLeft<OreAes128<R>>, OreError>;
+type VarCipherTextResult<R> = Result<VarCipherText<OreAes128<R>>, OreError>;
+
+/* Only encrypt the first max_len bytes of x (if given) */
+fn truncate(x: &[u8], max_len: Option<usize>) -> &[u8] {
+    match max_len {
+        Some(max_len) if max_len < x.len() => &x[..max_len],
+        _ => x,
+    }
+}
+
+impl<R: Rng + SeedableRng> OreAes128<R> {
+    /// Encrypt the left half of a variable length plaintext such as the bytes of a `str`.
+    ///
+    /// If `max_len` is given, only that many bytes of the plaintext are encrypted so all
+    /// plaintexts sharing the first `max_len` bytes compare as equal.
+    pub fn encrypt_left_var(&self, x: &[u8], max_len: Option<usize>) -> VarLeftResult<R> {
+        let x = truncate(x, max_len);
+        let mut output = VarLeft::<Self>::init(x.len());
+        self.keys
+            .prefix
+            .encrypt_left(VAR_DOMAIN, x, &mut output.xt, &mut output.f)?;
+
+        Ok(output)
+    }
+
+    /// Encrypt a variable length plaintext such as the bytes of a `str`.
+    ///
+    /// See [`Self::encrypt_left_var`] for how `max_len` is applied.
+    pub fn encrypt_var(&self, x: &[u8], max_len: Option<usize>) -> VarCipherTextResult<R> {
+        let x = truncate(x, max_len);
+        let mut left = VarLeft::<Self>::init(x.len());
+        let mut right = VarRight::<Self>::init(x.len());
+
+        // Generate a 16-byte random nonce
+        self.rng.borrow_mut().try_fill(&mut right.nonce)?;
+
+        self.keys.prefix.encrypt(
+            VAR_DOMAIN,
+            x,
+            &mut left.xt,
+            &mut left.f,
+            &right.nonce,
+            &mut right.data,
+        )?;
+
+        Ok(VarCipherText { left, right })
+    }
+
+    /// Recover the (possibly truncated) plaintext from a [`VarLeft`] ciphertext.
+    ///
+    /// Returns [`OreError::DecryptFailed`] if the ciphertext wasn't produced with this cipher's keys.
+    pub fn decrypt_left_var(&self, input: &VarLeft<Self>) -> Result<Vec<u8>, OreError> {
+        if input.f.len() != input.xt.len() {
+            return Err(OreError::DecryptFailed);
+        }
+
+        let mut x = vec![0; input.len()];
+        self.keys
+            .prefix
+            .decrypt_left(VAR_DOMAIN, &input.xt, &mut x)?;
+
+        // The PRP will happily invert anything so check the PRF blocks
+        let expected = self.encrypt_left_var(&x, None)?;
+        let mut is_valid = Choice::from(1);
+        for (a, b) in expected.f.iter().zip(input.f.iter()) {
+            is_valid &= a.ct_eq(b);
+        }
+
+        if bool::from(is_valid) {
+            Ok(x)
+        } else {
+            Err(OreError::DecryptFailed)
+        }
+    }
+
+    /// Recover the (possibly truncated) plaintext from a [`VarCipherText`].
+    pub fn decrypt_var(&self, input: &VarCipherText<Self>) -> Result<Vec<u8>, OreError> {
+        self.decrypt_left_var(&input.left)
+    }
+}
+
+/*
+ * Compare a left ciphertext against a full ciphertext of any length.
+ *
+ * The first differing block within the common prefix decides the ordering. If there isn't one,
+ * the shorter plaintext orders first.
+ */
+fn cmp_left_var<R: Rng + SeedableRng>(
+    a: &VarLeft<OreAes128<R>>,
+    b: &VarCipherText<OreAes128<R>>,
+) -> Ordering {
+    let mut is_equal = Choice::from(1);
+    let mut l: u64 = 0; // Unequal block
+
+    let blocks = a.xt.iter().zip(a.f.iter());
+    for (n, (xt, f)) in blocks
+        .zip(b.left.xt.iter().zip(b.left.f.iter()))
+        .enumerate()
+    {
+        let condition: Choice = !(xt.0.ct_eq(f.0)) | !(xt.1.ct_eq(f.1));
+
+        l.conditional_assign(&(n as u64), is_equal & condition);
+        is_equal.conditional_assign(&Choice::from(0), is_equal & condition);
+    }
+
+    let l: usize = l as usize;
+
+    if bool::from(is_equal) {
+        return a.len().cmp(&b.len());
+    }
+
+    let hash: Aes128Z2Hash = Hash::new(AesBlock::from_slice(&b.right.nonce));
+    let h = hash.hash(&a.f[l]);
+
+    let test = b.right.data[l].get_bit(a.xt[l] as usize) ^ h;
+    if test == 1 {
+        return Ordering::Greater;
+    }
+
+    Ordering::Less
+}
+
+impl<R: Rng + SeedableRng> VarOreCipher for OreAes128<R> {
+    fn compare_left_var(a: &VarLeft<Self>, b: &VarCipherText<Self>) -> Ordering {
+        cmp_left_var(a, b)
+    }
+
+    fn compare_var_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering> {
+        let num_blocks = var_blocks(a)?;
+
+        // The left ciphertext is always at the start of the full ciphertext
+        Self::compare_left_var_raw_slices(&a[0..num_blocks * (LeftBlock16::BLOCK_SIZE + 1)], b)
+    }
+
+    fn compare_left_var_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering> {
+        let a_blocks = a.len() / (LeftBlock16::BLOCK_SIZE + 1);
+        if a.len() != a_blocks * (LeftBlock16::BLOCK_SIZE + 1) {
+            return None;
+        }
+        let b_blocks = var_blocks(b)?;
+
+        let mut is_equal = Choice::from(1);
+        let mut l: u64 = 0; // Unequal block
+
+        // Slices for the PRF ("f") blocks
+        let a_f = &a[a_blocks..];
+        let b_f = &b[b_blocks..];
+
+        for n in 0..a_blocks.min(b_blocks) {
+            let prp_eq: Choice = !a[n].ct_eq(&b[n]);
+            let left_block_comparison: Choice = !left_block(a_f, n).ct_eq(left_block(b_f, n));
+            let condition: Choice = prp_eq | left_block_comparison;
+
+            l.conditional_assign(&(n as u64), is_equal & condition);
+            is_equal.conditional_assign(&Choice::from(0), is_equal & condition);
+        }
+
+        let l: usize = l as usize;
+
+        if bool::from(is_equal) {
+            return Some(a_blocks.cmp(&b_blocks));
+        }
+
+        let b_right = &b[b_blocks * (LeftBlock16::BLOCK_SIZE + 1)..];
+        let hash_key = HashKey::from_slice(&b_right[0..NONCE_SIZE]);
+        let hash: Aes128Z2Hash = Hash::new(hash_key);
+        let h = hash.hash(left_block(a_f, l));
+
+        let target_block = right_block(&b_right[NONCE_SIZE..], l);
+        let test = get_bit(target_block, a[l] as usize) ^ h;
+
+        if test == 1 {
+            return Some(Ordering::Greater);
+        }
+
+        Some(Ordering::Less)
+    }
+}
+
+/* The number of blocks in a serialized VarCipherText (None if it has an invalid length) */
+fn var_blocks(data: &[u8]) -> Option<usize> {
+    let block_size = LeftBlock16::BLOCK_SIZE + 1 + RightBlock32::BLOCK_SIZE;
+    let num_blocks = data.len().checked_sub(NONCE_SIZE)? / block_size;
+
+    if data.len() == num_blocks * block_size + NONCE_SIZE {
+        Some(num_blocks)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::OreAes128ChaCha20;
+    use super::*;
+    use crate::test_utils::init_ore;
+    use crate::OreCipher;
+
+    type Ore = OreAes128ChaCha20;
+
+    quickcheck! {
+        fn compare_strings(x: String, y: String) -> bool {
+            let ore: Ore = init_ore();
+            let a = ore.encrypt_var(x.as_bytes(), None).unwrap();
+            let b = ore.encrypt_var(y.as_bytes(), None).unwrap();
+
+            a.cmp(&b) == x.cmp(&y)
+        }
+
+        fn compare_left_bytes(x: Vec<u8>, y: Vec<u8>) -> bool {
+            let ore: Ore = init_ore();
+            let a = ore.encrypt_left_var(&x, None).unwrap();
+            let b = ore.encrypt_var(&y, None).unwrap();
+
+            a.partial_cmp(&b) == Some(x.cmp(&y))
+        }
+
+        fn compare_bytes_raw_slices(x: Vec<u8>, y: Vec<u8>) -> bool {
+            let ore: Ore = init_ore();
+            let a = ore.encrypt_var(&x, None).unwrap().to_bytes();
+            let b = ore.encrypt_var(&y, None).unwrap().to_bytes();
+
+            Ore::compare_var_raw_slices(&a, &b) == Some(x.cmp(&y))
+        }
+
+        fn compare_left_bytes_raw_slices(x: Vec<u8>, y: Vec<u8>) -> bool {
+            let ore: Ore = init_ore();
+            let a = ore.encrypt_left_var(&x, None).unwrap().to_bytes();
+            let b = ore.encrypt_var(&y, None).unwrap().to_bytes();
+
+            Ore::compare_left_var_raw_slices(&a, &b) == Some(x.cmp(&y))
+        }
+
+        fn compare_truncated(x: Vec<u8>, y: Vec<u8>) -> bool {
+            let ore: Ore = init_ore();
+            let a = ore.encrypt_var(&x, Some(4)).unwrap();
+            let b = ore.encrypt_var(&y, Some(4)).unwrap();
+
+            a.cmp(&b) == x[..x.len().min(4)].cmp(&y[..y.len().min(4)])
+        }
+
+        fn decrypt_string(x: String) -> bool {
+            let ore: Ore = init_ore();
+            let a = ore.encrypt_var(x.as_bytes(), None).unwrap();
+
+            ore.decrypt_var(&a).unwrap() == x.as_bytes()
+        }
+    }
+
+    #[test]
+    fn shorter_prefix_orders_first() {
+        let ore: Ore = init_ore();
+        let empty = ore.encrypt_var(b"", None).unwrap();
+        let a = ore.encrypt_var(b"app", None).unwrap();
+        let b = ore.encrypt_var(b"apple", None).unwrap();
+        let c = ore.encrypt_var(b"apply", None).unwrap();
+        let d = ore.encrypt_var(b"b", None).unwrap();
+
+        assert!(empty < a);
+        assert!(a < b);
+        assert!(b < c);
+        assert!(c < d);
+        assert!(ore.encrypt_left_var(b"apple", None).unwrap() == b);
+    }
+
+    #[test]
+    fn truncation() {
+        let ore: Ore = init_ore();
+        let a = ore.encrypt_var(b"abcdef", Some(3)).unwrap();
+        let b = ore.encrypt_var(b"abcxyz", Some(3)).unwrap();
+
+        assert_eq!(a.len(), 3);
+        assert!(a == b);
+        assert_eq!(ore.decrypt_var(&a).unwrap(), b"abc");
+    }
+
+    #[test]
+    fn binary_encoding() {
+        let ore: Ore = init_ore();
+        let a = ore.encrypt_var(b"hello", None).unwrap();
+        let bytes = a.to_bytes();
+
+        assert_eq!(bytes.len(), a.size());
+        let b = VarCipherText::<Ore>::from_slice(&bytes).unwrap();
+        assert!(a == b);
+        assert_eq!(b.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn binary_encoding_invalid_length() {
+        let ore: Ore = init_ore();
+        let bytes = ore.encrypt_var(b"hello", None).unwrap().to_bytes();
+
+        assert!(VarCipherText::<Ore>::from_slice(&bytes[1..]).is_err());
+        assert!(VarCipherText::<Ore>::from_slice(&bytes[..10]).is_err());
+        assert!(VarLeft::<Ore>::from_slice(&bytes[..20]).is_err());
+        assert!(VarRight::<Ore>::from_slice(&bytes[..10]).is_err());
+        assert_eq!(Ore::compare_var_raw_slices(&bytes, &bytes[1..]), None);
+        assert_eq!(Ore::compare_left_var_raw_slices(&bytes[1..], &bytes), None);
+        assert_eq!(Ore::compare_left_var_raw_slices(&[], &bytes[..10]), None);
+    }
+
+    #[test]
+    fn decrypt_with_wrong_keys() {
+        let ore: Ore = init_ore();
+        let other: Ore = OreCipher::init(&[1; 16], &[2; 16]).unwrap();
+        let a = ore.encrypt_left_var(b"secret", None).unwrap();
+
+        assert!(matches!(
+            other.decrypt_left_var(&a),
+            Err(OreError::DecryptFailed)
+        ));
+    }
+
+    #[test]
+    fn fixed_and_variable_domains_differ() {
+        let ore: Ore = init_ore();
+        let a = ore.encrypt_left(b"0123456789abcdef").unwrap();
+        let b = ore.encrypt_left_var(b"0123456789abcdef", None).unwrap();
+
+        assert_ne!(a.to_bytes(), b.to_bytes());
+    }
+}
@@ -0,0 +1,463 @@
+/*
+ * Block ORE Implemenation using a trinary (3-valued) indicator function
+ *
+ * Each right block encodes whether the corresponding plaintext block is less than, equal to or
+ * greater than every possible value of the left block. This means a left ciphertext can be
+ * compared directly against a right ciphertext so stored values only need their right
+ * ciphertexts (comparisons are then done against a query-side left ciphertext).
+ */
+
+use crate::{
+    ciphertext::*,
+    primitives::{hash::Aes128Z3Hash, AesBlock, Hash, HashKey, NONCE_SIZE},
+    scheme::prefixed::{IndicatorBlock, IndicatorKeys},
+    OreCipher, OreCompare, OreError, PlainText,
+};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use subtle_ng::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::ZeroizeOnDrop;
+
+pub mod block_types;
+pub use self::block_types::*;
+
+/* Define our scheme */
+#[derive(Debug, ZeroizeOnDrop)]
+pub struct OreAes128<R: Rng + SeedableRng> {
+    keys: IndicatorKeys,
+    #[zeroize(skip)]
+    rng: RefCell<R>,
+}
+
+pub type OreAes128ChaCha20 = OreAes128<ChaCha20Rng>;
+
+/* Define some convenience types */
+type EncryptLeftResult<R, const N: usize> = Result<Left<OreAes128<R>, N>, OreError>;
+type EncryptResult<R, const N: usize> = Result<CipherText<OreAes128<R>, N>, OreError>;
+type DecryptResult<const N: usize> = Result<PlainText<N>, OreError>;
+
+/* Separates the PrefixPrf outputs from other schemes and plaintext lengths */
+fn domain<const N: usize>() -> [u8; 8] {
+    let mut domain = *b"bit3\0\0\0\0";
+    domain[4..].copy_from_slice(&(N as u32).to_be_bytes());
+    domain
+}
+
+/*
+ * Trinary comparison: 0 when equal, 1 when a > b and 2 when a < b
+ */
+fn cmp(a: u8, b: u8) -> u8 {
+    u8::from(a > b) + 2 * u8::from(a < b)
+}
+
+impl IndicatorBlock for RightBlock52 {
+    type Hasher = Aes128Z3Hash;
+
+    fn indicator(jstar: u8, x: u8) -> u8 {
+        cmp(jstar, x)
+    }
+
+    fn set_masked(&mut self, j: usize, indicator: u8, h: u8) {
+        self.set_trit(j, (indicator + h) % 3);
+    }
+}
+
+impl<R: Rng + SeedableRng> OreCipher for OreAes128<R> {
+    type LeftBlockType = LeftBlock16;
+    type RightBlockType = RightBlock52;
+
+    fn init(k1: &[u8; 16], k2: &[u8; 16]) -> Result<Self, OreError> {
+        Self::init_with_rng(k1, k2, SeedableRng::from_entropy())
+    }
+
+    fn encrypt_left<const N: usize>(&self, x: &PlainText<N>) -> EncryptLeftResult<R, N> {
+        let mut output = Left::<Self, N>::init();
+        self.keys
+            .encrypt_left(&domain::<N>(), x, &mut output.xt, &mut output.f)?;
+
+        Ok(output)
+    }
+
+    fn encrypt<const N: usize>(&self, x: &PlainText<N>) -> EncryptResult<R, N> {
+        let mut left = Left::<Self, N>::init();
+        let mut right = Right::<Self, N>::init();
+
+        // Generate a 16-byte random nonce
+        self.rng.borrow_mut().try_fill(&mut right.nonce)?;
+
+        self.keys.encrypt(
+            &domain::<N>(),
+            x,
+            &mut left.xt,
+            &mut left.f,
+            &right.nonce,
+            &mut right.data,
+        )?;
+
+        Ok(CipherText { left, right })
+    }
+
+    fn decrypt_left<const N: usize>(&self, input: &Left<Self, N>) -> DecryptResult<N> {
+        self.keys.decrypt_left(&domain::<N>(), &input.xt, &input.f)
+    }
+
+    fn compare_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering> {
+        if a.len() != b.len() || a.len() < NONCE_SIZE {
+            return None;
+        };
+        let left_size = Self::LeftBlockType::BLOCK_SIZE;
+        let right_size = Self::RightBlockType::BLOCK_SIZE;
+        let num_blocks = (a.len() - NONCE_SIZE) / (left_size + right_size + 1);
+
+        Self::compare_left_raw_slices(&a[0..num_blocks * (left_size + 1)], b)
+    }
+
+    fn compare_left_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering> {
+        let left_size = Self::LeftBlockType::BLOCK_SIZE;
+        let num_blocks = a.len() / (left_size + 1);
+
+        if b.len() < num_blocks * (left_size + 1) {
+            return None;
+        }
+
+        // Only the right half of b is needed
+        Self::compare_left_right_raw_slices(a, &b[num_blocks * (left_size + 1)..])
+    }
+}
+
+impl<R: Rng + SeedableRng> OreEnvelopeScheme for OreAes128<R> {
+    const SCHEME_ID: u8 = 3;
+}
+
+impl<R: Rng + SeedableRng> OreAes128<R> {
+    /// Initialize a cipher which uses the given RNG to generate nonces for right ciphertexts.
+    ///
+    /// Ciphers initialized with the same keys and identical RNGs produce byte-identical ciphertexts
+    /// which is useful for test vectors. Use [`OreCipher::init`] everywhere else.
+    pub fn init_with_rng(k1: &[u8; 16], k2: &[u8; 16], rng: R) -> Result<Self, OreError> {
+        Ok(OreAes128 {
+            keys: IndicatorKeys::new(k1, k2),
+            rng: RefCell::new(rng),
+        })
+    }
+
+    /// Initialize a cipher with an RNG created from the given seed (see [`Self::init_with_rng`]).
+    pub fn init_with_seed(k1: &[u8; 16], k2: &[u8; 16], seed: R::Seed) -> Result<Self, OreError> {
+        Self::init_with_rng(k1, k2, R::from_seed(seed))
+    }
+
+    /*
+     * Compare a serialized Left ciphertext (a) against a serialized Right ciphertext (b) of the
+     * same number of blocks.
+     *
+     * Returns None if the slices don't have matching block counts or if a right block read
+     * during the comparison isn't validly packed.
+     */
+    pub(crate) fn compare_left_right_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering> {
+        let left_size = LeftBlock16::BLOCK_SIZE;
+        let right_size = RightBlock52::BLOCK_SIZE;
+        let num_blocks = a.len() / (left_size + 1);
+
+        if a.len() != num_blocks * (left_size + 1)
+            || b.len() != num_blocks * right_size + NONCE_SIZE
+        {
+            return None;
+        }
+
+        // Slices for the PRF ("f") blocks and right blocks
+        let a_f = &a[num_blocks..];
+        let b_data = &b[NONCE_SIZE..];
+
+        let hash: Aes128Z3Hash = Hash::new(HashKey::from_slice(&b[0..NONCE_SIZE]));
+        let mut first = FirstIndicator::new();
+        let mut valid = Choice::from(1);
+
+        for (n, xt) in a[0..num_blocks].iter().enumerate() {
+            let f_pos = n * left_size;
+            let h = hash.hash(&a_f[f_pos..(f_pos + left_size)]);
+
+            let r_pos = n * right_size;
+            let (trit, trit_valid) =
+                RightBlock52::trit_from_slice(&b_data[r_pos..(r_pos + right_size)], *xt as usize);
+            valid &= trit_valid;
+            first.update((trit + 3 - h) % 3);
+        }
+
+        if bool::from(valid) {
+            Some(first.ordering())
+        } else {
+            None
+        }
+    }
+}
+
+/*
+ * The result of the comparison is given by the first non-zero indicator
+ * (all blocks up to the first differing block will have an indicator of zero).
+ * Every indicator is visited so the position of the first differing block isn't leaked.
+ */
+struct FirstIndicator {
+    found: Choice,
+    result: u8,
+}
+
+impl FirstIndicator {
+    fn new() -> Self {
+        Self {
+            found: Choice::from(0),
+            result: 0,
+        }
+    }
+
+    fn update(&mut self, indicator: u8) {
+        let differs = !indicator.ct_eq(&0);
+        self.result
+            .conditional_assign(&indicator, !self.found & differs);
+        self.found |= differs;
+    }
+
+    fn ordering(&self) -> Ordering {
+        match self.result {
+            0 => Ordering::Equal,
+            1 => Ordering::Greater,
+            _ => Ordering::Less,
+        }
+    }
+}
+
+fn cmp_left_right<R: Rng + SeedableRng, const N: usize>(
+    a: &Left<OreAes128<R>, N>,
+    b: &Right<OreAes128<R>, N>,
+) -> Ordering {
+    let hash: Aes128Z3Hash = Hash::new(AesBlock::from_slice(&b.nonce));
+    let mut f = a.f;
+    let hashes = hash.hash_all(&mut f);
+
+    let mut first = FirstIndicator::new();
+    for ((block, xt), h) in b.data.iter().zip(a.xt.iter()).zip(hashes.iter()) {
+        first.update((block.get_trit(*xt as usize) + 3 - h) % 3);
+    }
+
+    first.ordering()
+}
+
+impl<R: Rng + SeedableRng> OreCompare for OreAes128<R> {
+    fn compare_left<const N: usize>(a: &Left<Self, N>, b: &CipherText<Self, N>) -> Ordering {
+        cmp_left_right(a, &b.right)
+    }
+}
+
+impl<R: Rng + SeedableRng, const N: usize> PartialEq<Right<OreAes128<R>, N>>
+    for Left<OreAes128<R>, N>
+{
+    fn eq(&self, b: &Right<OreAes128<R>, N>) -> bool {
+        matches!(cmp_left_right(self, b), Ordering::Equal)
+    }
+}
+
+impl<R: Rng + SeedableRng, const N: usize> PartialOrd<Right<OreAes128<R>, N>>
+    for Left<OreAes128<R>, N>
+{
+    fn partial_cmp(&self, b: &Right<OreAes128<R>, N>) -> Option<Ordering> {
+        Some(cmp_left_right(self, b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encrypt::OreEncrypt;
+
+    type Ore = OreAes128ChaCha20;
+
+    fn init_ore() -> Ore {
+        let mut k1: [u8; 16] = Default::default();
+        let mut k2: [u8; 16] = Default::default();
+
+        let mut rng = ChaCha20Rng::from_entropy();
+
+        rng.fill(&mut k1);
+        rng.fill(&mut k2);
+
+        OreCipher::init(&k1, &k2).unwrap()
+    }
+
+    quickcheck! {
+        fn compare_u64(x: u64, y: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_u64_raw_slices(x: u64, y: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap().to_bytes();
+            let b = y.encrypt(&ore).unwrap().to_bytes();
+
+            match Ore::compare_raw_slices(&a, &b) {
+                Some(Ordering::Greater) => x > y,
+                Some(Ordering::Less)    => x < y,
+                Some(Ordering::Equal)   => x == y,
+                None                    => false
+            }
+        }
+
+        fn compare_left_right_u64(x: u64, y: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt_left(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap().right;
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_left_right_u64_raw_slices(x: u64, y: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt_left(&ore).unwrap().to_bytes();
+            let b = y.encrypt(&ore).unwrap().right.to_bytes();
+
+            match Ore::compare_left_right_raw_slices(&a, &b) {
+                Some(Ordering::Greater) => x > y,
+                Some(Ordering::Less)    => x < y,
+                Some(Ordering::Equal)   => x == y,
+                None                    => false
+            }
+        }
+
+        fn compare_left_u64_raw_slices(x: u64, y: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt_left(&ore).unwrap().to_bytes();
+            let b = y.encrypt(&ore).unwrap().to_bytes();
+
+            match Ore::compare_left_raw_slices(&a, &b) {
+                Some(Ordering::Greater) => x > y,
+                Some(Ordering::Less)    => x < y,
+                Some(Ordering::Equal)   => x == y,
+                None                    => false
+            }
+        }
+
+        fn compare_u32(x: u32, y: u32) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_u128(x: u128, y: u128) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn decrypt_i128(x: i128) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+
+            i128::decrypt(&a, &ore).unwrap() == x
+        }
+
+        fn equality_u64(x: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = x.encrypt(&ore).unwrap();
+
+            a == b
+        }
+
+        fn decrypt_u64(x: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+
+            u64::decrypt(&a, &ore).unwrap() == x
+        }
+    }
+
+    #[test]
+    fn smallest_to_largest() {
+        let ore = init_ore();
+        let a = 0u64.encrypt(&ore).unwrap();
+        let b = u64::MAX.encrypt(&ore).unwrap();
+
+        assert!(a < b);
+        assert!(b > a);
+    }
+
+    #[test]
+    fn comparisons_in_last_block() {
+        let ore = init_ore();
+        let a = 10u64.encrypt_left(&ore).unwrap();
+        let b = 73u64.encrypt(&ore).unwrap();
+
+        assert!(a < b.right);
+        assert!(a != b.right);
+    }
+
+    #[test]
+    fn compare_left_right_raw_slices_mismatched_lengths() {
+        let ore = init_ore();
+        let a_64 = 10u64.encrypt_left(&ore).unwrap().to_bytes();
+        let a_32 = 10u32.encrypt(&ore).unwrap().right.to_bytes();
+
+        assert_eq!(Ore::compare_left_right_raw_slices(&a_64, &a_32), None);
+    }
+
+    #[test]
+    fn compare_left_right_raw_slices_invalid_right() {
+        let ore = init_ore();
+        let a = 10u32.encrypt_left(&ore).unwrap().to_bytes();
+        let mut b = 10u32.encrypt(&ore).unwrap().right.to_bytes();
+
+        assert_eq!(
+            Ore::compare_left_right_raw_slices(&a, &b),
+            Some(Ordering::Equal)
+        );
+
+        b[NONCE_SIZE..].iter_mut().for_each(|byte| *byte = 255);
+        assert_eq!(Ore::compare_left_right_raw_slices(&a, &b), None);
+    }
+
+    #[test]
+    fn binary_encoding() {
+        let ore = init_ore();
+        let a = 10u64.encrypt(&ore).unwrap();
+        let bin = a.to_bytes();
+        assert_eq!(a, CipherText::<Ore, 8>::from_slice(&bin).unwrap());
+    }
+
+    #[test]
+    fn init_with_seed_is_deterministic() {
+        let k1: [u8; 16] = rand::random();
+        let k2: [u8; 16] = rand::random();
+        let ore1 = Ore::init_with_seed(&k1, &k2, [7; 32]).unwrap();
+        let ore2 = Ore::init_with_seed(&k1, &k2, [7; 32]).unwrap();
+
+        let a = 1000u32.encrypt(&ore1).unwrap().to_bytes();
+        let b = 1000u32.encrypt(&ore2).unwrap().to_bytes();
+
+        assert_eq!(a, b);
+    }
+}
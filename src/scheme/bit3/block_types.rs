@@ -0,0 +1,109 @@
+use subtle_ng::{Choice, ConstantTimeLess};
+use zeroize::Zeroize;
+
+use crate::ciphertext::{CipherTextBlock, ParseError, ParseErrorReason};
+pub use crate::scheme::bit2::block_types::LeftBlock16;
+
+/* Powers of 3 for each of the 5 trits packed into a byte */
+const POW3: [u8; 5] = [1, 3, 9, 27, 81];
+
+/*
+ * Block type for a Right CipherText with 52-bytes per block
+ * corresponding to a plaintext block-size of 8-bits and a trinary indicator function.
+ *
+ * Each of the 256 indicator values is a trit (0, 1 or 2) and trits are packed 5 to a byte
+ * (3^5 = 243) so any byte of 243 or more is invalid.
+ */
+#[derive(Debug, Copy, Clone)]
+pub struct RightBlock52 {
+    data: [u8; 52],
+}
+
+impl Default for RightBlock52 {
+    fn default() -> Self {
+        Self { data: [0; 52] }
+    }
+}
+
+impl RightBlock52 {
+    /* Note that trits can only be set once on a fresh (zeroed) block */
+    #[inline]
+    pub fn set_trit(&mut self, trit: usize, value: u8) {
+        debug_assert!(trit < 256);
+        debug_assert!(value < 3);
+        self.data[trit / 5] += value * POW3[trit % 5];
+    }
+
+    #[inline]
+    pub fn get_trit(&self, trit: usize) -> u8 {
+        debug_assert!(trit < 256);
+        (self.data[trit / 5] / POW3[trit % 5]) % 3
+    }
+
+    /*
+     * Read a trit directly from the bytes of a serialized block without parsing it.
+     * The returned choice is false if the byte holding the trit isn't validly packed.
+     */
+    #[inline]
+    pub(crate) fn trit_from_slice(data: &[u8], trit: usize) -> (u8, Choice) {
+        debug_assert!(trit < 256);
+        let byte = data[trit / 5];
+        ((byte / POW3[trit % 5]) % 3, byte.ct_lt(&243))
+    }
+}
+
+impl CipherTextBlock for RightBlock52 {
+    const BLOCK_SIZE: usize = 52;
+
+    fn to_bytes(self) -> Vec<u8> {
+        self.data.to_vec()
+    }
+
+    fn from_bytes(data: &[u8]) -> Result<Self, ParseError> {
+        ParseError::check_length(Self::BLOCK_SIZE, data.len())?;
+        if data.iter().any(|&byte| byte >= 243) {
+            Err(ParseErrorReason::InvalidBlock.into())
+        } else {
+            let mut arr = [0; 52];
+            arr.clone_from_slice(data);
+
+            Ok(Self { data: arr })
+        }
+    }
+
+    fn default_in_place(&mut self) {
+        self.data.zeroize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get_trit() {
+        let mut block: RightBlock52 = Default::default();
+        for trit in 0..256 {
+            block.set_trit(trit, (trit % 3) as u8);
+        }
+
+        for trit in 0..256 {
+            assert_eq!(block.get_trit(trit), (trit % 3) as u8);
+
+            let (value, valid) = RightBlock52::trit_from_slice(&block.data, trit);
+            assert_eq!(value, (trit % 3) as u8);
+            assert!(bool::from(valid));
+        }
+    }
+
+    #[test]
+    fn from_bytes_rejects_invalid_packing() {
+        let mut bytes = [0u8; 52];
+        assert!(RightBlock52::from_bytes(&bytes).is_ok());
+
+        bytes[10] = 243;
+        assert!(RightBlock52::from_bytes(&bytes).is_err());
+        assert!(!bool::from(RightBlock52::trit_from_slice(&bytes, 50).1));
+        assert!(bool::from(RightBlock52::trit_from_slice(&bytes, 55).1));
+    }
+}
@@ -0,0 +1,409 @@
+/*
+ * Block ORE Implemenation using 4-bit plaintext blocks and a 2-bit indicator function
+ *
+ * Every plaintext byte is split into 2 blocks (high nibble first) so N is still the number of
+ * plaintext bytes. Right ciphertexts are 8 times smaller than with 8-bit blocks (4 bytes per
+ * plaintext byte) at the cost of larger left ciphertexts and twice as many blocks
+ * (i.e. more prefix leakage).
+ *
+ * Prefixes are passed to the PRFs with a PrefixPrf so there is no limit on the plaintext size.
+ */
+
+use crate::{
+    ciphertext::*,
+    primitives::{
+        hash::Aes128Z2Hash,
+        prf::{Aes128Prf, PrefixPrf},
+        prp::KnuthShufflePRP,
+        AesBlock, Hash, HashKey, Prf, Prp, NONCE_SIZE,
+    },
+    scheme::prefixed::PrefixKeys,
+    OreCipher, OreCompare, OreError, PlainText,
+};
+
+use aes::cipher::generic_array::GenericArray;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use subtle_ng::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::ZeroizeOnDrop;
+
+pub mod block_types;
+pub use self::block_types::*;
+
+/* Define our scheme */
+#[derive(Debug, ZeroizeOnDrop)]
+pub struct OreAes128<R: Rng + SeedableRng> {
+    prefix: PrefixKeys,
+    #[zeroize(skip)]
+    rng: RefCell<R>,
+}
+
+pub type OreAes128ChaCha20 = OreAes128<ChaCha20Rng>;
+
+/* Define some convenience types */
+type EncryptLeftResult<R, const N: usize> = Result<Left<OreAes128<R>, N>, OreError>;
+type EncryptResult<R, const N: usize> = Result<CipherText<OreAes128<R>, N>, OreError>;
+type DecryptResult<const N: usize> = Result<PlainText<N>, OreError>;
+
+/* Number of values in a 4-bit block */
+const BLOCK_DOMAIN: usize = 16;
+
+fn cmp(a: u8, b: u8) -> u8 {
+    u8::from(a > b)
+}
+
+/* Separates the PRF outputs from other schemes and plaintext lengths */
+fn domain<const N: usize>() -> [u8; 8] {
+    let mut domain = *b"bit4\0\0\0\0";
+    domain[4..].copy_from_slice(&(N as u32).to_be_bytes());
+    domain
+}
+
+/* Half 0 is the high nibble and half 1 is the low nibble */
+#[inline]
+fn nibble(byte: u8, half: usize) -> u8 {
+    (byte >> (4 * (1 - half))) & 0x0f
+}
+
+impl<R: Rng + SeedableRng> OreAes128<R> {
+    /// Initialize a cipher which uses the given RNG to generate nonces for right ciphertexts.
+    ///
+    /// Ciphers initialized with the same keys and identical RNGs produce byte-identical ciphertexts
+    /// which is useful for test vectors. Use [`OreCipher::init`] everywhere else.
+    pub fn init_with_rng(k1: &[u8; 16], k2: &[u8; 16], rng: R) -> Result<Self, OreError> {
+        let prf1: Aes128Prf = Prf::new(GenericArray::from_slice(k1));
+        let prf2: Aes128Prf = Prf::new(GenericArray::from_slice(k2));
+
+        Ok(OreAes128 {
+            prefix: PrefixKeys::new(&prf1, &prf2),
+            rng: RefCell::new(rng),
+        })
+    }
+
+    /// Initialize a cipher with an RNG created from the given seed (see [`Self::init_with_rng`]).
+    pub fn init_with_seed(k1: &[u8; 16], k2: &[u8; 16], seed: R::Seed) -> Result<Self, OreError> {
+        Self::init_with_rng(k1, k2, R::from_seed(seed))
+    }
+}
+
+impl<R: Rng + SeedableRng> OreCipher for OreAes128<R> {
+    type LeftBlockType = LeftBlock32;
+    type RightBlockType = RightBlock4;
+
+    fn init(k1: &[u8; 16], k2: &[u8; 16]) -> Result<Self, OreError> {
+        Self::init_with_rng(k1, k2, SeedableRng::from_entropy())
+    }
+
+    fn encrypt_left<const N: usize>(&self, x: &PlainText<N>) -> EncryptLeftResult<R, N> {
+        let mut output = Left::<Self, N>::init();
+        let mut prp_prefix = PrefixPrf::new(&self.prefix.prp, &domain::<N>());
+        let mut ro_prefix = PrefixPrf::new(&self.prefix.ro, &domain::<N>());
+
+        for (n, byte) in x.iter().enumerate() {
+            for half in 0..2 {
+                let xn = nibble(*byte, half);
+
+                // Create PRP for the block from the prefix
+                let prp: KnuthShufflePRP<u8, BLOCK_DOMAIN> = Prp::new(&prp_prefix.evaluate(&[]))?;
+                let xt = prp.permute(xn)?;
+
+                output.xt[n] |= xt << (4 * (1 - half));
+                *output.f[n].half_mut(half) = ro_prefix.evaluate(&[xt]);
+
+                prp_prefix.absorb(&[xn]);
+                ro_prefix.absorb(&[xn]);
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn encrypt<const N: usize>(&self, x: &PlainText<N>) -> EncryptResult<R, N> {
+        let mut left = Left::<Self, N>::init();
+        let mut right = Right::<Self, N>::init();
+
+        // Generate a 16-byte random nonce
+        self.rng.borrow_mut().try_fill(&mut right.nonce)?;
+
+        let mut prp_prefix = PrefixPrf::new(&self.prefix.prp, &domain::<N>());
+        let mut ro_prefix = PrefixPrf::new(&self.prefix.ro, &domain::<N>());
+        let hasher: Aes128Z2Hash = Hash::new(AesBlock::from_slice(&right.nonce));
+        let mut ro_keys: [AesBlock; BLOCK_DOMAIN] = Default::default();
+
+        for (n, byte) in x.iter().enumerate() {
+            for half in 0..2 {
+                let xn = nibble(*byte, half);
+
+                // Create PRP for the block from the prefix
+                let prp: KnuthShufflePRP<u8, BLOCK_DOMAIN> = Prp::new(&prp_prefix.evaluate(&[]))?;
+                let xt = prp.permute(xn)?;
+
+                left.xt[n] |= xt << (4 * (1 - half));
+                *left.f[n].half_mut(half) = ro_prefix.evaluate(&[xt]);
+
+                /*
+                 * The output of F in H(F(k1, y|i-1||j), r)
+                 */
+                ro_prefix.evaluate_all(&mut ro_keys, |j, value| value[0] = j as u8);
+                let hashes = hasher.hash_all(&mut ro_keys);
+
+                for (j, h) in hashes.iter().enumerate() {
+                    let jstar = prp.invert(j as u8)?;
+                    let indicator = cmp(jstar, xn);
+                    right.data[n].set_bit(half * BLOCK_DOMAIN + j, indicator ^ h);
+                }
+
+                prp_prefix.absorb(&[xn]);
+                ro_prefix.absorb(&[xn]);
+            }
+        }
+
+        Ok(CipherText { left, right })
+    }
+
+    fn decrypt_left<const N: usize>(&self, input: &Left<Self, N>) -> DecryptResult<N> {
+        let mut x: PlainText<N> = [0; N];
+        let mut prp_prefix = PrefixPrf::new(&self.prefix.prp, &domain::<N>());
+
+        // Recover the blocks in order, rebuilding each PRP from the recovered prefix
+        for (xn_byte, xt) in x.iter_mut().zip(input.xt.iter()) {
+            for half in 0..2 {
+                let prp: KnuthShufflePRP<u8, BLOCK_DOMAIN> = Prp::new(&prp_prefix.evaluate(&[]))?;
+                let xn = prp.invert(nibble(*xt, half))?;
+
+                *xn_byte |= xn << (4 * (1 - half));
+                prp_prefix.absorb(&[xn]);
+            }
+        }
+
+        // Check the PRF blocks to make sure the ciphertext was produced under these keys
+        let expected = self.encrypt_left(&x)?;
+        let mut is_valid = Choice::from(1);
+        for n in 0..N {
+            is_valid &= expected.f[n].ct_eq(&input.f[n]);
+        }
+
+        if bool::from(is_valid) {
+            Ok(x)
+        } else {
+            Err(OreError::DecryptFailed)
+        }
+    }
+
+    fn compare_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering> {
+        if a.len() != b.len() || a.len() < NONCE_SIZE {
+            return None;
+        };
+        let left_size = Self::LeftBlockType::BLOCK_SIZE;
+        let right_size = Self::RightBlockType::BLOCK_SIZE;
+        let num_blocks = (a.len() - NONCE_SIZE) / (left_size + right_size + 1);
+
+        Self::compare_left_raw_slices(&a[0..num_blocks * (left_size + 1)], b)
+    }
+
+    fn compare_left_raw_slices(a: &[u8], b: &[u8]) -> Option<Ordering> {
+        let left_size = Self::LeftBlockType::BLOCK_SIZE;
+        let right_size = Self::RightBlockType::BLOCK_SIZE;
+        let num_blocks = a.len() / (left_size + 1);
+
+        if a.len() != num_blocks * (left_size + 1)
+            || b.len() != num_blocks * (left_size + right_size + 1) + NONCE_SIZE
+        {
+            return None;
+        }
+
+        // Slices for the PRF ("f") blocks
+        let a_f = &a[num_blocks..];
+        let b_f = &b[num_blocks..];
+
+        let mut is_equal = Choice::from(1);
+        let mut l: u64 = 0; // Unequal 4-bit block
+
+        for k in 0..(num_blocks * 2) {
+            let (n, half) = (k / 2, k % 2);
+            let f_pos = n * left_size + half * 16;
+
+            let condition: Choice = !nibble(a[n], half).ct_eq(&nibble(b[n], half))
+                | !a_f[f_pos..(f_pos + 16)].ct_eq(&b_f[f_pos..(f_pos + 16)]);
+
+            l.conditional_assign(&(k as u64), is_equal & condition);
+            is_equal.conditional_assign(&Choice::from(0), is_equal & condition);
+        }
+
+        if bool::from(is_equal) {
+            return Some(Ordering::Equal);
+        }
+
+        let (n, half) = (l as usize / 2, l as usize % 2);
+        let f_pos = n * left_size + half * 16;
+
+        let b_right = &b[num_blocks * (left_size + 1)..];
+        let hash: Aes128Z2Hash = Hash::new(HashKey::from_slice(&b_right[0..NONCE_SIZE]));
+        let h = hash.hash(&a_f[f_pos..(f_pos + 16)]);
+
+        let target_block =
+            RightBlock4::from_bytes(&b_right[NONCE_SIZE + n * right_size..][..right_size]).ok()?;
+        let test = target_block.get_bit(half * BLOCK_DOMAIN + nibble(a[n], half) as usize) ^ h;
+
+        if test == 1 {
+            return Some(Ordering::Greater);
+        }
+
+        Some(Ordering::Less)
+    }
+}
+
+impl<R: Rng + SeedableRng> OreEnvelopeScheme for OreAes128<R> {
+    const SCHEME_ID: u8 = 4;
+}
+
+fn cmp_left<R: Rng + SeedableRng, const N: usize>(
+    a: &Left<OreAes128<R>, N>,
+    b: &CipherText<OreAes128<R>, N>,
+) -> Ordering {
+    let mut is_equal = Choice::from(1);
+    let mut l: u64 = 0; // Unequal 4-bit block
+
+    for k in 0..(N * 2) {
+        let (n, half) = (k / 2, k % 2);
+        let condition: Choice = !nibble(a.xt[n], half).ct_eq(&nibble(b.left.xt[n], half))
+            | !a.f[n].half(half).ct_eq(b.left.f[n].half(half));
+
+        l.conditional_assign(&(k as u64), is_equal & condition);
+        is_equal.conditional_assign(&Choice::from(0), is_equal & condition);
+    }
+
+    if bool::from(is_equal) {
+        return Ordering::Equal;
+    }
+
+    let (n, half) = (l as usize / 2, l as usize % 2);
+
+    let hash: Aes128Z2Hash = Hash::new(AesBlock::from_slice(&b.right.nonce));
+    let h = hash.hash(a.f[n].half(half));
+
+    let test = b.right.data[n].get_bit(half * BLOCK_DOMAIN + nibble(a.xt[n], half) as usize) ^ h;
+    if test == 1 {
+        return Ordering::Greater;
+    }
+
+    Ordering::Less
+}
+
+impl<R: Rng + SeedableRng> OreCompare for OreAes128<R> {
+    fn compare_left<const N: usize>(a: &Left<Self, N>, b: &CipherText<Self, N>) -> Ordering {
+        cmp_left(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encrypt::OreEncrypt;
+
+    type Ore = OreAes128ChaCha20;
+
+    fn init_ore() -> Ore {
+        let mut k1: [u8; 16] = Default::default();
+        let mut k2: [u8; 16] = Default::default();
+
+        let mut rng = ChaCha20Rng::from_entropy();
+
+        rng.fill(&mut k1);
+        rng.fill(&mut k2);
+
+        OreCipher::init(&k1, &k2).unwrap()
+    }
+
+    quickcheck! {
+        fn compare_u64(x: u64, y: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn compare_u64_raw_slices(x: u64, y: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap().to_bytes();
+            let b = y.encrypt(&ore).unwrap().to_bytes();
+
+            match Ore::compare_raw_slices(&a, &b) {
+                Some(Ordering::Greater) => x > y,
+                Some(Ordering::Less)    => x < y,
+                Some(Ordering::Equal)   => x == y,
+                None                    => false
+            }
+        }
+
+        fn compare_left_u32(x: u32, y: u32) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt_left(&ore).unwrap();
+            let b = y.encrypt(&ore).unwrap();
+
+            match x.cmp(&y) {
+                Ordering::Greater => a > b,
+                Ordering::Less    => a < b,
+                Ordering::Equal   => a == b
+            }
+        }
+
+        fn equality_u64(x: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+            let b = x.encrypt(&ore).unwrap();
+
+            a == b
+        }
+
+        fn decrypt_u64(x: u64) -> bool {
+            let ore = init_ore();
+            let a = x.encrypt(&ore).unwrap();
+
+            u64::decrypt(&a, &ore).unwrap() == x
+        }
+    }
+
+    #[test]
+    fn comparisons_in_low_nibble() {
+        let ore = init_ore();
+        let a = 0x10u32.encrypt(&ore).unwrap();
+        let b = 0x1fu32.encrypt(&ore).unwrap();
+
+        assert!(a < b);
+        assert!(b > a);
+    }
+
+    #[test]
+    fn right_ciphertext_size() {
+        assert_eq!(Right::<Ore, 8>::size(), 8 * 4 + NONCE_SIZE);
+    }
+
+    #[test]
+    fn binary_encoding() {
+        let ore = init_ore();
+        let a = 10u64.encrypt(&ore).unwrap();
+        let bin = a.to_bytes();
+        assert_eq!(a, CipherText::<Ore, 8>::from_slice(&bin).unwrap());
+    }
+
+    #[test]
+    fn init_with_seed_is_deterministic() {
+        let k1: [u8; 16] = rand::random();
+        let k2: [u8; 16] = rand::random();
+        let ore1 = Ore::init_with_seed(&k1, &k2, [7; 32]).unwrap();
+        let ore2 = Ore::init_with_seed(&k1, &k2, [7; 32]).unwrap();
+
+        let a = 1000u32.encrypt(&ore1).unwrap().to_bytes();
+        let b = 1000u32.encrypt(&ore2).unwrap().to_bytes();
+
+        assert_eq!(a, b);
+    }
+}
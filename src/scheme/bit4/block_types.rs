@@ -0,0 +1,132 @@
+use subtle_ng::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
+
+use crate::ciphertext::{CipherTextBlock, ParseError};
+use crate::primitives::AesBlock;
+
+/*
+ * Block type for a Left CipherText with 32-bytes per plaintext byte.
+ * Each plaintext byte is made up of 2 4-bit blocks so this holds one PRF output for each.
+ */
+#[derive(Debug, Copy, Clone, Default)]
+pub struct LeftBlock32 {
+    data: [AesBlock; 2],
+}
+
+impl LeftBlock32 {
+    #[inline]
+    pub fn half(&self, half: usize) -> &AesBlock {
+        &self.data[half]
+    }
+
+    #[inline]
+    pub fn half_mut(&mut self, half: usize) -> &mut AesBlock {
+        &mut self.data[half]
+    }
+}
+
+impl ConstantTimeEq for LeftBlock32 {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.data[0].ct_eq(&other.data[0]) & self.data[1].ct_eq(&other.data[1])
+    }
+}
+
+/*
+ * Block type for a Right CipherText with 4-bytes per plaintext byte
+ * corresponding to 2 4-bit plaintext blocks with a 2-bit indicator function (16 bits each).
+ * Bits 0-15 belong to the high nibble and bits 16-31 to the low nibble.
+ */
+#[derive(Debug, Copy, Clone, Default)]
+pub struct RightBlock4 {
+    data: [u8; 4],
+}
+
+impl RightBlock4 {
+    #[inline]
+    pub fn set_bit(&mut self, bit: usize, value: u8) {
+        debug_assert!(bit < 32);
+        let byte_index = bit / 8;
+        let mask = bit % 8;
+        let v = value << mask;
+        self.data[byte_index] |= v;
+    }
+
+    #[inline]
+    pub fn get_bit(&self, bit: usize) -> u8 {
+        debug_assert!(bit < 32);
+        let byte_index = bit / 8;
+        let position = bit % 8;
+        let v = 1 << position;
+
+        (self.data[byte_index] & v) >> position
+    }
+}
+
+impl CipherTextBlock for LeftBlock32 {
+    const BLOCK_SIZE: usize = 32;
+
+    fn to_bytes(self) -> Vec<u8> {
+        [self.data[0].to_vec(), self.data[1].to_vec()].concat()
+    }
+
+    fn from_bytes(data: &[u8]) -> Result<Self, ParseError> {
+        ParseError::check_length(Self::BLOCK_SIZE, data.len())?;
+        Ok(Self {
+            data: [
+                AesBlock::clone_from_slice(&data[0..16]),
+                AesBlock::clone_from_slice(&data[16..32]),
+            ],
+        })
+    }
+
+    fn default_in_place(&mut self) {
+        self.data[0].zeroize();
+        self.data[1].zeroize();
+    }
+}
+
+impl CipherTextBlock for RightBlock4 {
+    const BLOCK_SIZE: usize = 4;
+
+    fn to_bytes(self) -> Vec<u8> {
+        self.data.to_vec()
+    }
+
+    fn from_bytes(data: &[u8]) -> Result<Self, ParseError> {
+        ParseError::check_length(Self::BLOCK_SIZE, data.len())?;
+        let mut arr = [0; 4];
+        arr.clone_from_slice(data);
+
+        Ok(Self { data: arr })
+    }
+
+    fn default_in_place(&mut self) {
+        self.data.zeroize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get_bit() {
+        let mut block: RightBlock4 = Default::default();
+        block.set_bit(3, 1);
+        assert_eq!(block.get_bit(3), 1);
+        assert_eq!(block.get_bit(4), 0);
+
+        block.set_bit(31, 1);
+        assert_eq!(block.get_bit(31), 1);
+    }
+
+    #[test]
+    fn left_block_round_trip() {
+        let mut block: LeftBlock32 = Default::default();
+        block.half_mut(1)[15] = 42;
+
+        let parsed = LeftBlock32::from_bytes(&block.to_bytes()).unwrap();
+        assert!(bool::from(parsed.ct_eq(&block)));
+        assert_eq!(parsed.half(1)[15], 42);
+    }
+}
@@ -0,0 +1,355 @@
+/*
+ * The block layout shared by the bit2 and bit3 schemes which only differ in their right blocks
+ * (see IndicatorBlock) and how those are compared.
+ *
+ * Plaintexts longer than a single AES block (and variable length plaintexts) can't fit their
+ * prefixes into the PRF input so the PRP keys and left blocks are derived from a PrefixPrf
+ * instead. The PrefixPrfs are keyed with subkeys of k1 and k2 (see derive_prefix_prf) so their
+ * inputs can never collide with the single block layout which uses k1 and k2 directly.
+ * The bit4 and bit16 schemes only use PrefixPrfs and key them with the same subkeys.
+ */
+
+use crate::{
+    ciphertext::CipherTextBlock,
+    primitives::{
+        prf::{derive_prefix_prf, Aes128Prf, PrefixPrf},
+        prp::KnuthShufflePRP,
+        AesBlock, Hash, Prf, Prp, NONCE_SIZE,
+    },
+    OreError, PlainText,
+};
+use aes::cipher::generic_array::GenericArray;
+use lazy_static::lazy_static;
+use subtle_ng::{Choice, ConstantTimeEq};
+use zeroize::ZeroizeOnDrop;
+
+const PRP_KEY_LABEL: &[u8; 15] = b"ore-prefix-prp\0";
+const RO_KEY_LABEL: &[u8; 15] = b"ore-prefix-ro\0\0";
+
+/*
+ * The prefix, block value and block number for every block only fit into a single AES block for
+ * plaintexts of up to 15 bytes. Longer plaintexts pass their prefixes through a PrefixPrf instead.
+ */
+const MAX_SINGLE_BLOCK_PLAINTEXT: usize = 15;
+
+/*
+ * A right block which stores an indicator function masked by a hash of the left block
+ */
+pub(crate) trait IndicatorBlock {
+    type Hasher: Hash;
+
+    /* The indicator for a candidate value j* against the plaintext value x */
+    fn indicator(jstar: u8, x: u8) -> u8;
+
+    /* Store the indicator for value j masked with the hash h */
+    fn set_masked(&mut self, j: usize, indicator: u8, h: u8);
+}
+
+#[derive(Debug, ZeroizeOnDrop)]
+pub(crate) struct PrefixKeys {
+    /* Keys the PRPs (derived from k2) */
+    pub(crate) prp: Aes128Prf,
+    /* Keys the left blocks and random oracle inputs (derived from k1) */
+    pub(crate) ro: Aes128Prf,
+}
+
+impl PrefixKeys {
+    pub(crate) fn new(prf1: &Aes128Prf, prf2: &Aes128Prf) -> Self {
+        Self {
+            prp: derive_prefix_prf(prf2, PRP_KEY_LABEL),
+            ro: derive_prefix_prf(prf1, RO_KEY_LABEL),
+        }
+    }
+
+    /*
+     * Encrypt the left half of a plaintext of any length.
+     * The output slices must be the same length as the plaintext.
+     */
+    pub(crate) fn encrypt_left(
+        &self,
+        domain: &[u8],
+        x: &[u8],
+        xt: &mut [u8],
+        f: &mut [AesBlock],
+    ) -> Result<(), OreError> {
+        let mut prp_prefix = PrefixPrf::new(&self.prp, domain);
+        let mut ro_prefix = PrefixPrf::new(&self.ro, domain);
+
+        for (n, xn) in x.iter().enumerate() {
+            let prp: KnuthShufflePRP<u8, 256> = Prp::new(&prp_prefix.evaluate(&[]))?;
+
+            xt[n] = prp.permute(*xn)?;
+            f[n] = ro_prefix.evaluate(&[xt[n]]);
+
+            prp_prefix.absorb(&[*xn]);
+            ro_prefix.absorb(&[*xn]);
+        }
+
+        Ok(())
+    }
+
+    /*
+     * Same as encrypt_left but also computes the right blocks under the given nonce
+     */
+    pub(crate) fn encrypt<B: IndicatorBlock>(
+        &self,
+        domain: &[u8],
+        x: &[u8],
+        xt: &mut [u8],
+        f: &mut [AesBlock],
+        nonce: &[u8; NONCE_SIZE],
+        data: &mut [B],
+    ) -> Result<(), OreError> {
+        let mut prp_prefix = PrefixPrf::new(&self.prp, domain);
+        let mut ro_prefix = PrefixPrf::new(&self.ro, domain);
+        let hasher = B::Hasher::new(AesBlock::from_slice(nonce));
+        let mut ro_keys: [AesBlock; 256] = [Default::default(); 256];
+
+        for (n, xn) in x.iter().enumerate() {
+            let prp: KnuthShufflePRP<u8, 256> = Prp::new(&prp_prefix.evaluate(&[]))?;
+
+            xt[n] = prp.permute(*xn)?;
+            f[n] = ro_prefix.evaluate(&[xt[n]]);
+
+            /*
+             * The output of F in H(F(k1, y|i-1||j), r)
+             */
+            ro_prefix.evaluate_all(&mut ro_keys, |j, value| value[0] = j as u8);
+            let hashes = hasher.hash_all(&mut ro_keys);
+
+            for (j, h) in hashes.iter().enumerate() {
+                let jstar = prp.invert(j as u8)?;
+                data[n].set_masked(j, B::indicator(jstar, *xn), *h);
+            }
+
+            prp_prefix.absorb(&[*xn]);
+            ro_prefix.absorb(&[*xn]);
+        }
+
+        Ok(())
+    }
+
+    /*
+     * Invert the PRPs of a left ciphertext encrypted with encrypt_left.
+     * The PRF blocks are not checked so callers must verify them.
+     */
+    pub(crate) fn decrypt_left(
+        &self,
+        domain: &[u8],
+        xt: &[u8],
+        x: &mut [u8],
+    ) -> Result<(), OreError> {
+        let mut prp_prefix = PrefixPrf::new(&self.prp, domain);
+
+        for (xn, xt) in x.iter_mut().zip(xt.iter()) {
+            let prp: KnuthShufflePRP<u8, 256> = Prp::new(&prp_prefix.evaluate(&[]))?;
+            *xn = prp.invert(*xt)?;
+            prp_prefix.absorb(&[*xn]);
+        }
+
+        Ok(())
+    }
+}
+
+/*
+ * The keys of the bit2 and bit3 schemes. Plaintexts of up to MAX_SINGLE_BLOCK_PLAINTEXT bytes are
+ * encrypted with k1 and k2 directly and longer ones with the PrefixKeys.
+ */
+#[derive(Debug, ZeroizeOnDrop)]
+pub(crate) struct IndicatorKeys {
+    prf1: Aes128Prf,
+    prf2: Aes128Prf,
+    pub(crate) prefix: PrefixKeys,
+}
+
+impl IndicatorKeys {
+    pub(crate) fn new(k1: &[u8; 16], k2: &[u8; 16]) -> Self {
+        // TODO: k1 and k2 should be Key types and we should have a set of traits to abstract the
+        // behaviour ro parsing/loading etc
+
+        let prf1 = Prf::new(GenericArray::from_slice(k1));
+        let prf2 = Prf::new(GenericArray::from_slice(k2));
+
+        Self {
+            prefix: PrefixKeys::new(&prf1, &prf2),
+            prf1,
+            prf2,
+        }
+    }
+
+    pub(crate) fn encrypt_left<const N: usize>(
+        &self,
+        domain: &[u8],
+        x: &PlainText<N>,
+        xt: &mut [u8; N],
+        f: &mut [AesBlock; N],
+    ) -> Result<(), OreError> {
+        if N > MAX_SINGLE_BLOCK_PLAINTEXT {
+            return self.prefix.encrypt_left(domain, x, xt, f);
+        }
+
+        // Build the prefixes
+        f.iter_mut().enumerate().for_each(|(n, block)| {
+            block[0..n].clone_from_slice(&x[0..n]);
+            // TODO: Include the block number in the prefix to avoid repeating values for common
+            // blocks in a long prefix
+            // e.g. when plaintext is 4700 (2-bytes/blocks)
+            // xt = [17, 17, 17, 17, 17, 17, 223, 76]
+        });
+
+        self.prf2.encrypt_all(f);
+
+        for (n, xn) in x.iter().enumerate().take(N) {
+            // Set prefix and create PRP for the block
+            let prp: KnuthShufflePRP<u8, 256> = Prp::new(&f[n])?;
+
+            xt[n] = prp.permute(*xn)?;
+        }
+
+        // Reset the f block
+        // We don't actually need to clear sensitive data here, we
+        // just need fast "zero set". Reassigning the value will drop the old one and allocate new
+        // data to the stack
+        *f = [Default::default(); N];
+
+        for n in 0..N {
+            f[n][0..n].clone_from_slice(&x[0..n]);
+            f[n][n] = xt[n];
+            // Include the block number in the value passed to the Random Oracle
+            f[n][N] = n as u8;
+        }
+        self.prf1.encrypt_all(f);
+
+        Ok(())
+    }
+
+    /*
+     * Same as encrypt_left but also computes the right blocks under the given nonce
+     */
+    pub(crate) fn encrypt<B: IndicatorBlock, const N: usize>(
+        &self,
+        domain: &[u8],
+        x: &PlainText<N>,
+        xt: &mut [u8; N],
+        f: &mut [AesBlock; N],
+        nonce: &[u8; NONCE_SIZE],
+        data: &mut [B; N],
+    ) -> Result<(), OreError> {
+        if N > MAX_SINGLE_BLOCK_PLAINTEXT {
+            return self.prefix.encrypt(domain, x, xt, f, nonce, data);
+        }
+
+        // Build the prefixes
+        f.iter_mut().enumerate().for_each(|(n, block)| {
+            block[0..n].clone_from_slice(&x[0..n]);
+        });
+
+        self.prf2.encrypt_all(f);
+
+        // To make zeroizing / resetting the RO keys
+        // Since the AesBlock type is stack allocated this should get optimised to a single memcpy
+        lazy_static! {
+            static ref ZEROED_RO_KEYS: [AesBlock; 256] = [Default::default(); 256];
+        }
+
+        let mut ro_keys = *ZEROED_RO_KEYS;
+
+        for n in 0..N {
+            // Set prefix and create PRP for the block
+            let prp: KnuthShufflePRP<u8, 256> = Prp::new(&f[n])?;
+
+            xt[n] = prp.permute(x[n])?;
+
+            // Reset the f block
+            f[n].default_in_place();
+
+            f[n][0..n].clone_from_slice(&x[0..n]);
+            f[n][n] = xt[n];
+            // Include the block number in the value passed to the Random Oracle
+            f[n][N] = n as u8;
+
+            for (j, ro_key) in ro_keys.iter_mut().enumerate() {
+                /*
+                 * The output of F in H(F(k1, y|i-1||j), r)
+                 */
+                ro_key[0..n].clone_from_slice(&x[0..n]);
+                ro_key[n] = j as u8;
+                ro_key[N] = n as u8;
+            }
+
+            self.prf1.encrypt_all(&mut ro_keys);
+
+            /* TODO: This seems to work but it is technically using the nonce as the key
+             * (instead of using it as the plaintext). This appears to be how the original
+             * ORE implementation does it but it feels a bit wonky to me. Should check with David.
+             * It is useful though because the AES crate makes it easy to encrypt groups of 8
+             * plaintexts under the same key. We really want the ability to encrypt the same
+             * plaintext (i.e. the nonce) under different keys but this may be an acceptable
+             * approximation.
+             *
+             * If not, we will probably need to implement our own parallel encrypt using intrisics
+             * like in the AES crate: https://github.com/RustCrypto/block-ciphers/blob/master/aes/src/ni/aes128.rs#L26
+             */
+            let hasher = B::Hasher::new(AesBlock::from_slice(nonce));
+            let hashes = hasher.hash_all(&mut ro_keys);
+
+            // FIXME: force casting to u8 from usize could cause a panic
+            for (j, h) in hashes.iter().enumerate() {
+                let jstar = prp.invert(j as u8)?;
+                data[n].set_masked(j, B::indicator(jstar, x[n]), *h);
+            }
+
+            // Zeroize / reset the RO keys before the next loop iteration
+            ro_keys.clone_from_slice(&*ZEROED_RO_KEYS);
+        }
+
+        self.prf1.encrypt_all(f);
+
+        Ok(())
+    }
+
+    /*
+     * Recover the plaintext of a left ciphertext encrypted with encrypt_left.
+     * Fails if the PRF blocks don't match the ones encrypt_left produces for the plaintext.
+     */
+    pub(crate) fn decrypt_left<const N: usize>(
+        &self,
+        domain: &[u8],
+        xt: &[u8; N],
+        f: &[AesBlock; N],
+    ) -> Result<PlainText<N>, OreError> {
+        let mut x: PlainText<N> = [0; N];
+
+        if N > MAX_SINGLE_BLOCK_PLAINTEXT {
+            self.prefix.decrypt_left(domain, xt, &mut x)?;
+        } else {
+            // Each PRP is keyed by the prefix of the plaintext so we have to recover the blocks in
+            // order, rebuilding the PRP for the next block from the ones we've already recovered
+            for n in 0..N {
+                let mut prefix: [AesBlock; 1] = Default::default();
+                prefix[0][0..n].clone_from_slice(&x[0..n]);
+                self.prf2.encrypt_all(&mut prefix);
+
+                let prp: KnuthShufflePRP<u8, 256> = Prp::new(&prefix[0])?;
+                x[n] = prp.invert(xt[n])?;
+            }
+        }
+
+        // The PRP will happily invert anything so check the PRF blocks to make sure the
+        // ciphertext was actually produced under these keys
+        let mut expected_xt = [0; N];
+        let mut expected_f = [Default::default(); N];
+        self.encrypt_left(domain, &x, &mut expected_xt, &mut expected_f)?;
+
+        let mut is_valid = Choice::from(1);
+        for (expected, actual) in expected_f.iter().zip(f.iter()) {
+            is_valid &= expected.ct_eq(actual);
+        }
+
+        if bool::from(is_valid) {
+            Ok(x)
+        } else {
+            Err(OreError::DecryptFailed)
+        }
+    }
+}